@@ -24,9 +24,13 @@ extern crate substrate_codec as codec;
 extern crate substrate_runtime_io as runtime_io;
 extern crate substrate_client as client;
 extern crate substrate_executor as substrate_executor;
+extern crate substrate_runtime_democracy as democracy;
 extern crate substrate_runtime_executive;
+extern crate substrate_runtime_staking as staking;
+extern crate substrate_runtime_support as runtime_support;
 extern crate substrate_primitives;
 extern crate substrate_runtime_primitives as runtime_primitives;
+extern crate futures;
 extern crate substrate_state_machine as state_machine;
 
 #[macro_use]
@@ -17,12 +17,18 @@
 //! Strongly typed API for light Polkadot client.
 
 use std::sync::Arc;
+use futures::{Future, IntoFuture};
 use client::backend::{Backend, RemoteBackend};
-use client::{Client, CallExecutor};
+use client::light::Fetcher;
+use client::{self, Client, CallExecutor, RemoteCallExecutor};
 use codec::Slicable;
+use democracy::{ReferendumIndex, ReferendumInfoOf, VoteThreshold};
+use runtime_io::twox_128;
+use runtime_support::{StorageMap, StorageValue};
+use staking::ReservedBalance;
 use state_machine;
-use primitives::{AccountId, Block, BlockId, Hash, Index, SessionKey, Timestamp, UncheckedExtrinsic};
-use runtime::Address;
+use primitives::{AccountId, Balance, Block, BlockId, BlockNumber, Hash, Index, SessionKey, Timestamp, UncheckedExtrinsic};
+use runtime::{ActiveCouncil, Address, Concrete, ParachainHeads, PrivCall};
 use primitives::parachain::{CandidateReceipt, DutyRoster, Id as ParaId};
 use full::CheckedId;
 use {PolkadotApi, BlockBuilder, RemotePolkadotApi, CheckedBlockId, Result, ErrorKind};
@@ -41,6 +47,17 @@ impl BlockBuilder for LightBlockBuilder {
 	}
 }
 
+/// Referendum information, as stored by the democracy module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferendumInfo {
+	/// Block at which the referendum ends.
+	pub end: BlockNumber,
+	/// Proposal being voted on.
+	pub proposal: PrivCall,
+	/// Threshold the proposal needs to be approved with.
+	pub threshold: VoteThreshold,
+}
+
 /// Remote polkadot API implementation.
 pub struct RemotePolkadotApiWrapper<B: Backend<Block>, E: CallExecutor<Block>>(pub Arc<Client<B, E, Block>>);
 
@@ -113,3 +130,235 @@ impl<B: Backend<Block>, E: CallExecutor<Block>> PolkadotApi for RemotePolkadotAp
 impl<B: RemoteBackend<Block>, E: CallExecutor<Block>> RemotePolkadotApi for RemotePolkadotApiWrapper<B, E>
 	where ::client::error::Error: From<<<B as Backend<Block>>::State as state_machine::backend::Backend>::Error>
 {}
+
+impl<B, F> RemotePolkadotApiWrapper<B, RemoteCallExecutor<B, F>>
+	where
+		B: RemoteBackend<Block> + 'static,
+		F: Fetcher<Block> + 'static,
+		<F::RemoteReadResult as IntoFuture>::Future: 'static,
+		::client::error::Error: From<<<B as Backend<Block>>::State as state_machine::backend::Backend>::Error>,
+{
+	/// Read referendum information from the remote node, checking it against the read proof.
+	/// Resolves to `None` if there's no referendum with given index at given block.
+	pub fn remote_referendum(&self, at: &CheckedId, index: ReferendumIndex) -> Box<Future<Item=Option<ReferendumInfo>, Error=client::error::Error>> {
+		let key = ReferendumInfoOf::<Concrete>::key_for(&index);
+		Box::new(self.remote_read(at, &key).and_then(|value| match value {
+			Some(value) => <(BlockNumber, PrivCall, VoteThreshold)>::decode(&mut &value[..])
+				.map(|(end, proposal, threshold)| Some(ReferendumInfo { end, proposal, threshold }))
+				.ok_or_else(|| client::error::ErrorKind::Backend("error decoding referendum info".into()).into()),
+			None => Ok(None),
+		}))
+	}
+
+	/// Read the reserved balance of given account from the remote node, checking it against the
+	/// read proof. Accounts without reserved funds have zero reserved balance.
+	pub fn remote_reserved_balance(&self, at: &CheckedId, account: AccountId) -> Box<Future<Item=Balance, Error=client::error::Error>> {
+		let key = ReservedBalance::<Concrete>::key_for(&account);
+		Box::new(self.remote_read(at, &key).and_then(|value| match value {
+			Some(value) => Balance::decode(&mut &value[..])
+				.ok_or_else(|| client::error::ErrorKind::Backend("error decoding reserved balance".into()).into()),
+			None => Ok(0),
+		}))
+	}
+
+	/// Read the head data of given parachain from the remote node, checking it against the read
+	/// proof. Resolves to `None` if the parachain has no head at given block.
+	pub fn remote_para_head(&self, at: &CheckedId, parachain: ParaId) -> Box<Future<Item=Option<Vec<u8>>, Error=client::error::Error>> {
+		let key = ParachainHeads::<Concrete>::key_for(&parachain);
+		Box::new(self.remote_read(at, &key).and_then(|value| match value {
+			Some(value) => Vec::<u8>::decode(&mut &value[..])
+				.map(Some)
+				.ok_or_else(|| client::error::ErrorKind::Backend("error decoding parachain head".into()).into()),
+			None => Ok(None),
+		}))
+	}
+
+	/// Read the members of the active council from the remote node, checking them against the read
+	/// proof. Resolves to an empty list if no council is set at given block.
+	pub fn remote_council_members(&self, at: &CheckedId) -> Box<Future<Item=Vec<AccountId>, Error=client::error::Error>> {
+		let key = ActiveCouncil::<Concrete>::key();
+		Box::new(self.remote_read(at, key).and_then(|value| match value {
+			Some(value) => Vec::<(AccountId, BlockNumber)>::decode(&mut &value[..])
+				.map(|council| council.into_iter().map(|(member, _)| member).collect())
+				.ok_or_else(|| client::error::ErrorKind::Backend("error decoding active council".into()).into()),
+			None => Ok(Vec::new()),
+		}))
+	}
+
+	fn remote_read(&self, at: &CheckedId, key: &[u8]) -> Box<Future<Item=Option<Vec<u8>>, Error=client::error::Error>> {
+		self.0.executor().remote_read(at.block_id(), twox_128(key).to_vec())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::future;
+	use client::LocalCallExecutor;
+	use client::in_mem::Backend as InMemory;
+	use client::light::{self, FetchChecker, LightDataChecker, RemoteBatchReadRequest, RemoteCallRequest,
+		RemoteChtRequest, RemoteHeaderByHashRequest, RemoteReadRequest};
+	use democracy;
+	use keyring::Keyring;
+	use runtime::{self, GenesisConfig, ConsensusConfig, BuildStorage};
+	use runtime_primitives::StorageMap as GenesisStorage;
+	use polkadot_executor::Executor as LocalDispatch;
+	use substrate_executor::{NativeExecutor, NativeExecutionDispatch};
+
+	type FullClient = Client<InMemory<Block>, LocalCallExecutor<InMemory<Block>, NativeExecutor<LocalDispatch>>, Block>;
+	type LightClient = Client<light::Backend<Block>, RemoteCallExecutor<light::Backend<Block>, LocalFetcher>, Block>;
+
+	/// Serves remote reads from the full client, checking the proofs with the light data checker.
+	struct LocalFetcher {
+		full: FullClient,
+		checker: LightDataChecker<NativeExecutor<LocalDispatch>, Block>,
+	}
+
+	impl Fetcher<Block> for LocalFetcher {
+		type RemoteCallResult = future::FutureResult<client::CallResult, client::error::Error>;
+		type RemoteReadResult = future::FutureResult<Option<Vec<u8>>, client::error::Error>;
+		type RemoteBatchReadResult = future::FutureResult<Vec<Option<Vec<u8>>>, client::error::Error>;
+		type RemoteHeaderResult = future::FutureResult<::primitives::Header, client::error::Error>;
+		type RemoteChtResult = future::FutureResult<Vec<Hash>, client::error::Error>;
+
+		fn remote_call(&self, _request: RemoteCallRequest<Hash>) -> Self::RemoteCallResult {
+			future::err(client::error::ErrorKind::Backend("remote calls are not supported".into()).into())
+		}
+
+		fn remote_read(&self, request: RemoteReadRequest<Hash>) -> Self::RemoteReadResult {
+			self.full.read_proof(&BlockId::Hash(request.block), &request.key)
+				.and_then(|proof| self.checker.check_read_proof(&request, proof))
+				.into_future()
+		}
+
+		fn remote_read_batch(&self, request: RemoteBatchReadRequest<Hash>) -> Self::RemoteBatchReadResult {
+			self.full.read_batch_proof(&BlockId::Hash(request.block), &request.keys)
+				.and_then(|proof| self.checker.check_read_batch_proof(&request, proof))
+				.into_future()
+		}
+
+		fn remote_header_by_hash(&self, request: RemoteHeaderByHashRequest<Hash>) -> Self::RemoteHeaderResult {
+			self.full.header(&BlockId::Hash(request.hash))
+				.and_then(|header| self.checker.check_header_by_hash_proof(&request, header))
+				.into_future()
+		}
+
+		fn remote_cht(&self, request: RemoteChtRequest) -> Self::RemoteChtResult {
+			self.full.cht_hashes(request.segment)
+				.and_then(|hashes| self.checker.check_cht_proof(&request, hashes))
+				.into_future()
+		}
+	}
+
+	fn referendum() -> ReferendumInfo {
+		ReferendumInfo {
+			end: 10,
+			proposal: PrivCall::Democracy(democracy::PrivCall::cancel_referendum(1)),
+			threshold: VoteThreshold::SuperMajorityApprove,
+		}
+	}
+
+	fn genesis_storage() -> GenesisStorage {
+		let mut storage = GenesisConfig {
+			consensus: Some(ConsensusConfig {
+				code: LocalDispatch::native_equivalent().to_vec(),
+				authorities: vec![Keyring::One.to_raw_public()],
+			}),
+			system: None,
+			session: Some(Default::default()),
+			council: Some(Default::default()),
+			democracy: Some(Default::default()),
+			parachains: Some(Default::default()),
+			staking: Some(Default::default()),
+		}.build_storage();
+
+		let info = referendum();
+		storage.insert(
+			twox_128(&ReferendumInfoOf::<Concrete>::key_for(&0)).to_vec(),
+			(info.end, info.proposal, info.threshold).encode(),
+		);
+		let reserved: AccountId = Keyring::One.to_raw_public().into();
+		storage.insert(twox_128(&ReservedBalance::<Concrete>::key_for(&reserved)).to_vec(), 1_000u128.encode());
+		storage.insert(twox_128(&ParachainHeads::<Concrete>::key_for(&ParaId::from(5))).to_vec(), vec![1u8, 2, 3].encode());
+		storage
+	}
+
+	fn light_api() -> RemotePolkadotApiWrapper<light::Backend<Block>, RemoteCallExecutor<light::Backend<Block>, LocalFetcher>> {
+		light_api_with(genesis_storage())
+	}
+
+	fn light_api_with(storage: GenesisStorage) -> RemotePolkadotApiWrapper<light::Backend<Block>, RemoteCallExecutor<light::Backend<Block>, LocalFetcher>> {
+		let full = ::client::new_in_mem(LocalDispatch::new(), storage.clone()).unwrap();
+		let backend = light::new_light_backend();
+		let checker = light::new_fetch_checker(backend.clone(), LocalDispatch::new());
+		let fetcher = Arc::new(LocalFetcher { full, checker });
+		let client: LightClient = light::new_light(backend, fetcher, storage).unwrap();
+		RemotePolkadotApiWrapper(Arc::new(client))
+	}
+
+	#[test]
+	fn reads_active_referendum() {
+		let api = light_api();
+		let id = api.check_id(BlockId::number(0)).unwrap();
+		assert_eq!(api.remote_referendum(&id, 0).wait().unwrap(), Some(referendum()));
+	}
+
+	#[test]
+	fn reads_unknown_referendum() {
+		let api = light_api();
+		let id = api.check_id(BlockId::number(0)).unwrap();
+		assert_eq!(api.remote_referendum(&id, 1).wait().unwrap(), None);
+	}
+
+	#[test]
+	fn reads_reserved_balance() {
+		let api = light_api();
+		let id = api.check_id(BlockId::number(0)).unwrap();
+		assert_eq!(api.remote_reserved_balance(&id, Keyring::One.to_raw_public().into()).wait().unwrap(), 1_000);
+	}
+
+	#[test]
+	fn reads_zero_reserved_balance() {
+		let api = light_api();
+		let id = api.check_id(BlockId::number(0)).unwrap();
+		assert_eq!(api.remote_reserved_balance(&id, Keyring::Two.to_raw_public().into()).wait().unwrap(), 0);
+	}
+
+	#[test]
+	fn reads_para_head() {
+		let api = light_api();
+		let id = api.check_id(BlockId::number(0)).unwrap();
+		assert_eq!(api.remote_para_head(&id, 5.into()).wait().unwrap(), Some(vec![1, 2, 3]));
+	}
+
+	#[test]
+	fn reads_unregistered_para_head() {
+		let api = light_api();
+		let id = api.check_id(BlockId::number(0)).unwrap();
+		assert_eq!(api.remote_para_head(&id, 6.into()).wait().unwrap(), None);
+	}
+
+	#[test]
+	fn reads_council_members() {
+		let members: Vec<AccountId> = vec![Keyring::One.to_raw_public().into(), Keyring::Two.to_raw_public().into()];
+		let mut storage = genesis_storage();
+		storage.insert(
+			twox_128(ActiveCouncil::<Concrete>::key()).to_vec(),
+			members.iter().map(|member| (member.clone(), 10 as BlockNumber)).collect::<Vec<_>>().encode(),
+		);
+
+		let api = light_api_with(storage);
+		let id = api.check_id(BlockId::number(0)).unwrap();
+		assert_eq!(api.remote_council_members(&id).wait().unwrap(), members);
+	}
+
+	#[test]
+	fn reads_unset_council_as_empty() {
+		let mut storage = genesis_storage();
+		storage.remove(&twox_128(ActiveCouncil::<Concrete>::key()).to_vec());
+
+		let api = light_api_with(storage);
+		let id = api.check_id(BlockId::number(0)).unwrap();
+		assert_eq!(api.remote_council_members(&id).wait().unwrap(), Vec::<AccountId>::new());
+	}
+}
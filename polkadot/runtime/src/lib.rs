@@ -76,6 +76,8 @@ pub use runtime_primitives::BuildStorage;
 pub use consensus::Call as ConsensusCall;
 pub use timestamp::Call as TimestampCall;
 pub use parachains::Call as ParachainsCall;
+pub use parachains::Heads as ParachainHeads;
+pub use council::ActiveCouncil;
 pub use primitives::Header;
 
 /// The position of the timestamp set extrinsic.
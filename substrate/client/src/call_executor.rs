@@ -15,18 +15,18 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
-use futures::{IntoFuture, Future};
+use futures::{future, IntoFuture, Future};
 use runtime_primitives::generic::BlockId;
-use runtime_primitives::traits::{Block as BlockT, Header as HeaderT};
+use runtime_primitives::traits::{Block as BlockT, Header as HeaderT, Hashing as HashingT, HashingFor};
 use state_machine::{self, OverlayedChanges, Backend as StateBackend, CodeExecutor};
 
 use backend;
 use blockchain::Backend as ChainBackend;
 use error;
-use light::{Fetcher, RemoteCallRequest};
+use light::{Fetcher, ReadOutcome, RemoteCallRequest, RemoteReadRequest};
 
 /// Information regarding the result of a call.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CallResult {
 	/// The data that was returned from the call.
 	pub return_data: Vec<u8>,
@@ -128,6 +128,68 @@ impl<B, F> RemoteCallExecutor<B, F> {
 	pub fn new(backend: Arc<B>, fetcher: Arc<F>) -> Self {
 		RemoteCallExecutor { backend, fetcher }
 	}
+
+	/// Get fetcher reference.
+	pub fn fetcher(&self) -> &Arc<F> {
+		&self.fetcher
+	}
+
+	/// Read storage value at given block from remote node.
+	///
+	/// When the block is referenced by number and the read fails after that block has been
+	/// reorged out, the read is retried once at the new canonical block with the same number.
+	pub fn remote_read<Block>(&self, id: &BlockId<Block>, key: Vec<u8>) -> Box<Future<Item=Option<Vec<u8>>, Error=error::Error>>
+		where
+			B: backend::RemoteBackend<Block> + 'static,
+			F: Fetcher<Block> + 'static,
+			Block: BlockT,
+			<F::RemoteReadResult as IntoFuture>::Future: 'static,
+	{
+		let number = match *id {
+			BlockId::Hash(block) => return Box::new(self.fetcher.remote_read(RemoteReadRequest { block, key, max_proof_size: None, retry: None }).into_future()),
+			BlockId::Number(number) => number,
+		};
+		let block = match self.backend.blockchain().hash(number) {
+			Ok(Some(block)) => block,
+			Ok(None) => return Box::new(future::err(error::ErrorKind::UnknownBlock(format!("{}", number)).into())),
+			Err(error) => return Box::new(future::err(error)),
+		};
+
+		let backend = self.backend.clone();
+		let fetcher = self.fetcher.clone();
+		let request = RemoteReadRequest { block, key: key.clone(), max_proof_size: None, retry: None };
+		Box::new(self.fetcher.remote_read(request).into_future().or_else(move |error| {
+			match backend.blockchain().hash(number) {
+				Ok(Some(canonical)) if canonical != block => {
+					trace!("Retrying remote read at #{}: {} has been replaced by {}", number, block, canonical);
+					future::Either::A(fetcher.remote_read(RemoteReadRequest { block: canonical, key, max_proof_size: None, retry: None }).into_future())
+				},
+				_ => future::Either::B(future::err(error)),
+			}
+		}))
+	}
+
+	/// Read storage value at given block from remote node, comparing it against the hash of the
+	/// value the caller already has.
+	///
+	/// The expected hash is not sent to the remote node: the read proof carries the value itself,
+	/// so it is always fetched, since the remote node can not be trusted to report that a value
+	/// is unchanged without proving it. A conditional read therefore costs as much as a
+	/// `remote_read`, and only saves the caller from comparing the values.
+	pub fn remote_read_conditional<Block>(&self, id: &BlockId<Block>, key: Vec<u8>, known_hash: Block::Hash) -> Box<Future<Item=ReadOutcome, Error=error::Error>>
+		where
+			B: backend::RemoteBackend<Block> + 'static,
+			F: Fetcher<Block> + 'static,
+			Block: BlockT,
+			<F::RemoteReadResult as IntoFuture>::Future: 'static,
+	{
+		Box::new(self.remote_read(id, key).map(move |value| {
+			match value {
+				Some(ref value) if HashingFor::<Block>::hash(value) == known_hash => ReadOutcome::Unchanged,
+				value => ReadOutcome::Changed(value),
+			}
+		}))
+	}
 }
 
 impl<B, F, Block> CallExecutor<Block> for RemoteCallExecutor<B, F>
@@ -150,6 +212,8 @@ impl<B, F, Block> CallExecutor<Block> for RemoteCallExecutor<B, F>
 			block: block_hash,
 			method: method.into(),
 			call_data: call_data.to_vec(),
+			expected_result: None,
+			retry: None,
 		}).into_future().wait()
 	}
 
@@ -177,12 +241,15 @@ pub fn check_execution_proof<B, E, Block>(backend: &B, executor: &E, request: &R
 	do_check_execution_proof(local_state_root, executor, request, remote_proof)
 }
 
-/// Check remote execution proof using given state root.
+/// Check remote execution proof using given state root. Failures name the method and the block of
+/// the request.
 fn do_check_execution_proof<E, H>(local_state_root: H, executor: &E, request: &RemoteCallRequest<H>, remote_proof: Vec<Vec<u8>>) -> Result<CallResult, error::Error>
 	where
 		E: CodeExecutor,
-		H: Into<[u8; 32]>, // TODO: remove when patricia_trie generic.
+		H: Into<[u8; 32]> + ::std::fmt::Debug, // TODO: remove when patricia_trie generic.
 {
+	let invalid_proof = || error::ErrorKind::InvalidExecutionProofFor(request.method.clone(), format!("{:?}", request.block));
+
 	let mut changes = OverlayedChanges::default();
 	let (local_result, _) = state_machine::proof_check(
 		local_state_root.into(),
@@ -190,18 +257,81 @@ fn do_check_execution_proof<E, H>(local_state_root: H, executor: &E, request: &R
 		&mut changes,
 		executor,
 		&request.method,
-		&request.call_data)?;
+		&request.call_data)
+		.map_err(|e| error::Error::with_chain(error::Error::from(e), invalid_proof()))?;
+
+	// a result other than the expected one is an `InvalidExecutionProof`, naming the call in its cause.
+	if let Some(ref expected_result) = request.expected_result {
+		if *expected_result != local_result {
+			return Err(error::Error::with_chain(error::Error::from(invalid_proof()), error::ErrorKind::InvalidExecutionProof));
+		}
+	}
 
 	Ok(CallResult { return_data: local_result, changes })
 }
 
 #[cfg(test)]
 mod tests {
+	use std::sync::Arc;
+	use futures::{future, Future};
+	use parking_lot::Mutex;
 	use runtime_primitives::generic::BlockId;
+	use runtime_primitives::traits::{Hashing as HashingT, HashingFor, Header as HeaderT};
 	use state_machine::Backend;
-	use test_client;
-	use light::RemoteCallRequest;
-	use super::do_check_execution_proof;
+	use test_client::{self, runtime::{Block, Hash, Header}};
+	use backend::{Backend as ClientBackend, BlockImportOperation};
+	use error;
+	use fetcher::testing::TestFetcher;
+	use light::{self, Fetcher, ReadOutcome, RemoteBatchReadRequest, RemoteCallRequest, RemoteChtRequest,
+		RemoteHeaderByHashRequest, RemoteReadRequest};
+	use super::{do_check_execution_proof, CallResult, RemoteCallExecutor};
+
+	/// Fetcher which reorgs the light chain to `reorged` while serving the first read.
+	struct ReorgingFetcher {
+		backend: Arc<light::Backend<Block>>,
+		reorged: Header,
+		requests: Mutex<Vec<Hash>>,
+	}
+
+	impl Fetcher<Block> for ReorgingFetcher {
+		type RemoteCallResult = future::FutureResult<CallResult, error::Error>;
+		type RemoteReadResult = future::FutureResult<Option<Vec<u8>>, error::Error>;
+		type RemoteBatchReadResult = future::FutureResult<Vec<Option<Vec<u8>>>, error::Error>;
+		type RemoteHeaderResult = future::FutureResult<Header, error::Error>;
+		type RemoteChtResult = future::FutureResult<Vec<Hash>, error::Error>;
+
+		fn remote_call(&self, _request: RemoteCallRequest<Hash>) -> Self::RemoteCallResult {
+			future::err(error::ErrorKind::NotAvailableOnLightClient.into())
+		}
+
+		fn remote_read_batch(&self, _request: RemoteBatchReadRequest<Hash>) -> Self::RemoteBatchReadResult {
+			future::err(error::ErrorKind::NotAvailableOnLightClient.into())
+		}
+
+		fn remote_header_by_hash(&self, _request: RemoteHeaderByHashRequest<Hash>) -> Self::RemoteHeaderResult {
+			future::err(error::ErrorKind::NotAvailableOnLightClient.into())
+		}
+
+		fn remote_cht(&self, _request: RemoteChtRequest) -> Self::RemoteChtResult {
+			future::err(error::ErrorKind::NotAvailableOnLightClient.into())
+		}
+
+		fn remote_read(&self, request: RemoteReadRequest<Hash>) -> Self::RemoteReadResult {
+			self.requests.lock().push(request.block);
+			if request.block == self.reorged.hash() {
+				return future::ok(Some(vec![42]));
+			}
+
+			import_header(&self.backend, self.reorged.clone());
+			future::err(error::ErrorKind::Backend("state root mismatch".into()).into())
+		}
+	}
+
+	fn import_header(backend: &light::Backend<Block>, header: Header) {
+		let mut op = backend.begin_operation(BlockId::Hash(Default::default())).unwrap();
+		op.set_block_data(header, None, None, true).unwrap();
+		backend.commit_operation(op).unwrap();
+	}
 
 	#[test]
 	fn execution_proof_is_generated_and_checked() {
@@ -220,6 +350,103 @@ mod tests {
 			block: Default::default(),
 			method: "authorities".into(),
 			call_data: vec![],
+			expected_result: None,
+			retry: None,
 		}, remote_execution_proof).unwrap();
 	}
+
+	#[test]
+	fn execution_proof_is_checked_against_expected_result() {
+		let remote_client = test_client::new();
+		let remote_block_id = BlockId::Number(0);
+		let remote_block_storage_root = remote_client.state_at(&remote_block_id)
+			.unwrap().storage_root(::std::iter::empty()).0;
+		let (remote_result, remote_execution_proof) = remote_client.execution_proof(&remote_block_id, "authorities", &[]).unwrap();
+
+		let local_executor = test_client::NativeExecutor::new();
+		let check = |expected_result: Option<Vec<u8>>| do_check_execution_proof(remote_block_storage_root, &local_executor, &RemoteCallRequest {
+			block: Default::default(),
+			method: "authorities".into(),
+			call_data: vec![],
+			expected_result,
+			retry: None,
+		}, remote_execution_proof.clone());
+
+		assert_eq!(check(Some(remote_result.clone())).unwrap().return_data, remote_result);
+		let error = check(Some(vec![42])).unwrap_err();
+		match *error.kind() {
+			error::ErrorKind::InvalidExecutionProof => (),
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+		assert!(error.iter().any(|e| format!("{}", e).contains("authorities")));
+	}
+
+	#[test]
+	fn invalid_execution_proof_names_method() {
+		let remote_client = test_client::new();
+		let remote_block_id = BlockId::Number(0);
+		let remote_block_storage_root = remote_client.state_at(&remote_block_id)
+			.unwrap().storage_root(::std::iter::empty()).0;
+		let remote_execution_proof = remote_client.execution_proof(&remote_block_id, "authorities", &[]).unwrap().1;
+
+		// the proof is checked against the wrong root.
+		let local_executor = test_client::NativeExecutor::new();
+		let mut wrong_root = remote_block_storage_root;
+		wrong_root[0] ^= 1;
+		let error = do_check_execution_proof(wrong_root, &local_executor, &RemoteCallRequest {
+			block: Default::default(),
+			method: "authorities".into(),
+			call_data: vec![],
+			expected_result: None,
+			retry: None,
+		}, remote_execution_proof).unwrap_err();
+		assert!(format!("{}", error).contains("authorities"));
+	}
+
+	#[test]
+	fn remote_read_is_retried_after_reorg() {
+		let original: Header = HeaderT::new(1, Default::default(), [1u8; 32].into(), Default::default(), Default::default());
+		let reorged: Header = HeaderT::new(1, Default::default(), [2u8; 32].into(), Default::default(), Default::default());
+		let backend = light::new_light_backend();
+		import_header(&backend, original.clone());
+
+		let fetcher = Arc::new(ReorgingFetcher { backend: backend.clone(), reorged: reorged.clone(), requests: Mutex::new(Vec::new()) });
+		let executor = RemoteCallExecutor::new(backend, fetcher.clone());
+		assert_eq!(executor.remote_read(&BlockId::Number(1), b"key".to_vec()).wait().unwrap(), Some(vec![42]));
+		assert_eq!(*fetcher.requests.lock(), vec![original.hash(), reorged.hash()]);
+	}
+
+	#[test]
+	fn conditional_remote_read_compares_known_hash() {
+		let reorged: Header = HeaderT::new(1, Default::default(), [2u8; 32].into(), Default::default(), Default::default());
+		let backend = light::new_light_backend();
+		import_header(&backend, reorged.clone());
+
+		let fetcher = TestFetcher::<Block>::default();
+		fetcher.insert_read(&RemoteReadRequest { block: reorged.hash(), key: b"key".to_vec(), max_proof_size: None, retry: None }, Some(vec![42]));
+		let executor = RemoteCallExecutor::new(backend, Arc::new(fetcher));
+		let at = BlockId::Hash(reorged.hash());
+
+		let known_hash = HashingFor::<Block>::hash(&[42]);
+		assert_eq!(executor.remote_read_conditional(&at, b"key".to_vec(), known_hash).wait().unwrap(), ReadOutcome::Unchanged);
+
+		let stale_hash = HashingFor::<Block>::hash(&[24]);
+		assert_eq!(executor.remote_read_conditional(&at, b"key".to_vec(), stale_hash).wait().unwrap(), ReadOutcome::Changed(Some(vec![42])));
+	}
+
+	#[test]
+	fn conditional_remote_read_fetches_unchanged_value() {
+		let header: Header = HeaderT::new(1, Default::default(), [2u8; 32].into(), Default::default(), Default::default());
+		let backend = light::new_light_backend();
+		import_header(&backend, header.clone());
+
+		let fetcher = TestFetcher::<Block>::default();
+		fetcher.insert_read(&RemoteReadRequest { block: header.hash(), key: b"key".to_vec(), max_proof_size: None, retry: None }, Some(vec![42]));
+		let executor = RemoteCallExecutor::new(backend, Arc::new(fetcher.clone()));
+
+		// the value is fetched even though the caller already has it.
+		let known_hash = HashingFor::<Block>::hash(&[42]);
+		assert_eq!(executor.remote_read_conditional(&BlockId::Hash(header.hash()), b"key".to_vec(), known_hash).wait().unwrap(), ReadOutcome::Unchanged);
+		assert_eq!(fetcher.requests(), 1);
+	}
 }
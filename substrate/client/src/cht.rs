@@ -0,0 +1,74 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Canonical hash tries. A CHT maps the blocks of a range, by their offset in the range, to their
+//! hashes, so that a single root commits to the whole canonical range.
+
+use runtime_primitives::traits::{Hashing, ordered_trie_key};
+
+/// Number of blocks in a CHT segment. Segment `n` covers blocks `n * SIZE .. (n + 1) * SIZE`.
+pub const SIZE: u64 = 2048;
+
+/// Range of block numbers covered by the CHT `segment`, as the first block and the last one. `None`
+/// if the segment is beyond the range of block numbers.
+pub fn segment_range(segment: u64) -> Option<(u64, u64)> {
+	let first = segment.checked_mul(SIZE)?;
+	let end = segment.checked_add(1)?.checked_mul(SIZE)?;
+	Some((first, end - 1))
+}
+
+/// Root of the CHT over `hashes`, the hashes of consecutive blocks starting at the range start.
+pub fn build_cht_root<H: Hashing>(hashes: &[H::Output]) -> H::Output {
+	H::ordered_trie_root(hashes.iter())
+}
+
+/// Key of the block at `offset` from the range start in the CHT, as it is proven against the root.
+pub fn cht_key_for(offset: u64) -> Vec<u8> {
+	ordered_trie_key(offset as usize)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use runtime_primitives::traits::{BlakeTwo256, Hashing};
+	use state_machine::{backend::InMemory, prove_read, read_proof_check};
+	use super::{build_cht_root, cht_key_for, segment_range, SIZE};
+
+	#[test]
+	fn segment_range_is_checked() {
+		assert_eq!(segment_range(0), Some((0, SIZE - 1)));
+		assert_eq!(segment_range(3), Some((3 * SIZE, 4 * SIZE - 1)));
+		assert_eq!(segment_range(u64::max_value() / SIZE - 1), Some((u64::max_value() / SIZE * SIZE - SIZE, u64::max_value() / SIZE * SIZE - 1)));
+		assert_eq!(segment_range(u64::max_value() / SIZE), None);
+		assert_eq!(segment_range(u64::max_value()), None);
+	}
+
+	#[test]
+	fn cht_proof_is_checked_against_root() {
+		let hashes: Vec<_> = (0..200u64).map(|n| BlakeTwo256::hash_of(&n)).collect();
+		let root = build_cht_root::<BlakeTwo256>(&hashes);
+
+		// full node side: the CHT as storage, proving the key of block #130.
+		let storage: HashMap<_, _> = hashes.iter().enumerate()
+			.map(|(offset, hash)| (cht_key_for(offset as u64), hash.0.to_vec()))
+			.collect();
+		let proof = prove_read(InMemory::from(storage), &cht_key_for(130)).unwrap().1;
+
+		// light client side.
+		assert_eq!(read_proof_check(root.0, proof.clone(), &cht_key_for(130)).unwrap(), Some(hashes[130].0.to_vec()));
+		assert!(read_proof_check(build_cht_root::<BlakeTwo256>(&hashes[1..]).0, proof, &cht_key_for(130)).is_err());
+	}
+}
@@ -21,7 +21,7 @@ use futures::sync::mpsc;
 use parking_lot::{Mutex, RwLock};
 use primitives::AuthorityId;
 use runtime_primitives::{bft::Justification, generic::BlockId};
-use runtime_primitives::traits::{Block as BlockT, Header as HeaderT, Zero, One};
+use runtime_primitives::traits::{As, Block as BlockT, Header as HeaderT, Zero, One};
 use runtime_primitives::BuildStorage;
 use primitives::storage::{StorageKey, StorageData};
 use codec::Slicable;
@@ -228,6 +228,32 @@ impl<B, E, Block: BlockT> Client<B, E, Block> where
 		self.state_at(id).and_then(|state| self.executor.prove_at_state(state, &mut Default::default(), method, call_data))
 	}
 
+	/// Reads storage value at a given block + key, returning read proof.
+	pub fn read_proof(&self, id: &BlockId<Block>, key: &[u8]) -> error::Result<Vec<Vec<u8>>> {
+		self.state_at(id).and_then(|state| ::light::prove_read(state, key))
+	}
+
+	/// Reads storage values at a given block + keys, returning single read proof for all keys.
+	pub fn read_batch_proof(&self, id: &BlockId<Block>, keys: &[Vec<u8>]) -> error::Result<Vec<Vec<u8>>> {
+		self.state_at(id)
+			.and_then(|state| state_machine::prove_read_batch(state, keys)
+				.map(|(_, proof)| ::light::versioned_proof(proof))
+				.map_err(Into::into))
+	}
+
+	/// Hashes of the blocks of the CHT `segment`, which together prove themselves against the
+	/// segment's CHT root. Fails if the segment is not complete in the local chain.
+	pub fn cht_hashes(&self, segment: u64) -> error::Result<Vec<Block::Hash>> {
+		let (first, last) = ::cht::segment_range(segment)
+			.ok_or_else(|| error::ErrorKind::UnknownChtSegment(segment))?;
+		(first..last + 1)
+			.map(|number| -> error::Result<Block::Hash> {
+				self.block_hash(As::sa(number as usize))?
+					.ok_or_else(|| error::ErrorKind::UnknownChtSegment(segment).into())
+			})
+			.collect()
+	}
+
 	/// Set up the native execution environment to call into a native runtime code.
 	pub fn using_environment<F: FnOnce() -> T, T>(
 		&self, f: F
@@ -492,6 +518,18 @@ mod tests {
 		assert_eq!(client.using_environment(|| test_runtime::system::balance_of(Keyring::Ferdie.to_raw_public().into())).unwrap(), 0);
 	}
 
+	#[test]
+	fn cht_hashes_of_unknown_segment_fail() {
+		let client = test_client::new();
+
+		for segment in vec![0, 1, u64::max_value()] {
+			match *client.cht_hashes(segment).unwrap_err().kind() {
+				error::ErrorKind::UnknownChtSegment(s) => assert_eq!(s, segment),
+				ref kind => panic!("unexpected error: {:?}", kind),
+			}
+		}
+	}
+
 	#[test]
 	fn authorities_call_works() {
 		let client = test_client::new();
@@ -94,11 +94,77 @@ error_chain! {
 			display("Remote node has responded with invalid execution proof"),
 		}
 
+		/// Invalid remote proof of the call of given method at given block.
+		InvalidExecutionProofFor(method: String, block: String) {
+			description("invalid execution proof"),
+			display("Remote node has responded with invalid execution proof of {} at block {}", &*method, &*block),
+		}
+
+		/// Finalized header does not advance the highest known finalized block.
+		NonMonotonicFinality(number: String, best: String) {
+			description("non-monotonic finality"),
+			display("Finalized block {} is not above the best known finalized block {}", &*number, &*best),
+		}
+
+		/// Block is not known to be on the finalized chain.
+		NotFinalizedChain(h: String) {
+			description("block is not on the finalized chain"),
+			display("Block {} is not known to be on the finalized chain", &*h),
+		}
+
+		/// Remote proof exceeds the size budget of the request.
+		ProofTooLarge(size: usize, max: usize) {
+			description("remote proof is too large"),
+			display("Remote proof of {} bytes exceeds the limit of {} bytes", size, max),
+		}
+
+		/// Remote proof is of a format version this client can't check.
+		UnsupportedProofVersion(version: u8) {
+			description("unsupported remote proof version"),
+			display("Remote proof is of unsupported format version {}", version),
+		}
+
+		/// Header does not extend the chain of its claimed parent.
+		InvalidHeaderAncestry(h: String) {
+			description("header does not descend from its claimed parent"),
+			display("Header {} is not a child of its claimed parent", &*h),
+		}
+
+		/// Header sync can not be resumed from the checkpoint.
+		CheckpointMismatch(checkpoint: String, h: String) {
+			description("header does not descend from the sync checkpoint"),
+			display("Header {} is not a child of the checkpointed header {}", &*h, &*checkpoint),
+		}
+
+		/// Remote header does not match the requested hash.
+		InvalidRemoteHeader(h: String) {
+			description("invalid remote header"),
+			display("Remote node has responded with a header which does not hash to {}", &*h),
+		}
+
+		/// CHT segment is not covered by the known chain, or its root is not known.
+		UnknownChtSegment(segment: u64) {
+			description("unknown CHT segment"),
+			display("CHT segment {} is beyond the known chain", segment),
+		}
+
+		/// Remote CHT segment hashes do not build the known CHT root.
+		InvalidChtProof(segment: u64) {
+			description("invalid CHT proof"),
+			display("Remote node has responded with hashes which do not match the root of CHT segment {}", segment),
+		}
+
 		/// Invalid remote proof.
 		RemoteFetchCancelled {
 			description("remote fetch cancelled"),
 			display("Remote data fetch has been cancelled"),
 		}
+
+		/// Remote fetch has not completed in time.
+		RemoteFetchTimeout {
+			description("remote fetch timeout"),
+			display("Remote data fetch has timed out"),
+		}
 	}
 }
 
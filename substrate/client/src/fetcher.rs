@@ -0,0 +1,971 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Light client data fetcher wrappers.
+
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use futures::{future, Future, IntoFuture};
+use futures::future::Either;
+use linked_hash_map::LinkedHashMap;
+use parking_lot::Mutex;
+use tokio_timer::{Timer, TimerError};
+use runtime_primitives::traits::Block as BlockT;
+use call_executor::CallResult;
+use error;
+use light::{Fetcher, RemoteBatchReadRequest, RemoteCallRequest, RemoteChtRequest, RemoteHeaderByHashRequest, RemoteReadRequest,
+	RetryPolicy};
+
+/// Fetcher which remembers the results of the inner fetcher, serving repeated requests without
+/// going to the network.
+///
+/// Storage reads, headers and CHT segments are cached by default. Calls are only cached after
+/// `with_call_caching`, since the caller may depend on the call being executed remotely. Failed
+/// requests are never cached.
+pub struct CachingFetcher<B: BlockT, F> {
+	inner: F,
+	cache_calls: bool,
+	calls: Arc<Mutex<Cache<(B::Hash, String, Vec<u8>), CallResult>>>,
+	reads: Arc<Mutex<Cache<(B::Hash, Vec<u8>), Option<Vec<u8>>>>>,
+	batch_reads: Arc<Mutex<Cache<(B::Hash, Vec<Vec<u8>>), Vec<Option<Vec<u8>>>>>>,
+	headers: Arc<Mutex<Cache<B::Hash, B::Header>>>,
+	chts: Arc<Mutex<Cache<u64, Vec<B::Hash>>>>,
+}
+
+/// Least recently used cache, also dropping entries older than its time to live.
+struct Cache<K: Hash + Eq, V> {
+	entries: LinkedHashMap<K, (Instant, V)>,
+	capacity: usize,
+	ttl: Duration,
+}
+
+impl<B: BlockT, F> CachingFetcher<B, F> {
+	/// Create a new caching fetcher, keeping at most `capacity` results of each request kind for
+	/// at most `ttl`.
+	pub fn new(inner: F, capacity: usize, ttl: Duration) -> Self {
+		CachingFetcher {
+			inner,
+			cache_calls: false,
+			calls: Arc::new(Mutex::new(Cache::new(capacity, ttl))),
+			reads: Arc::new(Mutex::new(Cache::new(capacity, ttl))),
+			batch_reads: Arc::new(Mutex::new(Cache::new(capacity, ttl))),
+			headers: Arc::new(Mutex::new(Cache::new(capacity, ttl))),
+			chts: Arc::new(Mutex::new(Cache::new(capacity, ttl))),
+		}
+	}
+
+	/// Cache the results of remote calls too.
+	pub fn with_call_caching(mut self) -> Self {
+		self.cache_calls = true;
+		self
+	}
+}
+
+impl<B, F> Fetcher<B> for CachingFetcher<B, F> where
+	B: BlockT,
+	F: Fetcher<B>,
+	<F::RemoteCallResult as IntoFuture>::Future: 'static,
+	<F::RemoteReadResult as IntoFuture>::Future: 'static,
+	<F::RemoteBatchReadResult as IntoFuture>::Future: 'static,
+	<F::RemoteHeaderResult as IntoFuture>::Future: 'static,
+	<F::RemoteChtResult as IntoFuture>::Future: 'static,
+{
+	type RemoteCallResult = Box<Future<Item=CallResult, Error=error::Error>>;
+	type RemoteReadResult = Box<Future<Item=Option<Vec<u8>>, Error=error::Error>>;
+	type RemoteBatchReadResult = Box<Future<Item=Vec<Option<Vec<u8>>>, Error=error::Error>>;
+	type RemoteHeaderResult = Box<Future<Item=B::Header, Error=error::Error>>;
+	type RemoteChtResult = Box<Future<Item=Vec<B::Hash>, Error=error::Error>>;
+
+	fn remote_call(&self, request: RemoteCallRequest<B::Hash>) -> Self::RemoteCallResult {
+		if !self.cache_calls {
+			return Box::new(self.inner.remote_call(request).into_future());
+		}
+
+		let key = (request.block, request.method.clone(), request.call_data.clone());
+		cached(&self.calls, key, || self.inner.remote_call(request))
+	}
+
+	fn remote_read(&self, request: RemoteReadRequest<B::Hash>) -> Self::RemoteReadResult {
+		let key = (request.block, request.key.clone());
+		cached(&self.reads, key, || self.inner.remote_read(request))
+	}
+
+	fn remote_read_batch(&self, request: RemoteBatchReadRequest<B::Hash>) -> Self::RemoteBatchReadResult {
+		let key = (request.block, request.keys.clone());
+		cached(&self.batch_reads, key, || self.inner.remote_read_batch(request))
+	}
+
+	fn remote_header_by_hash(&self, request: RemoteHeaderByHashRequest<B::Hash>) -> Self::RemoteHeaderResult {
+		let key = request.hash;
+		cached(&self.headers, key, || self.inner.remote_header_by_hash(request))
+	}
+
+	fn remote_cht(&self, request: RemoteChtRequest) -> Self::RemoteChtResult {
+		let key = request.segment;
+		cached(&self.chts, key, || self.inner.remote_cht(request))
+	}
+}
+
+/// Serve `key` from `cache`, or `fetch` it and cache the result once it has been fetched.
+fn cached<K, V, R, Fetch>(cache: &Arc<Mutex<Cache<K, V>>>, key: K, fetch: Fetch) -> Box<Future<Item=V, Error=error::Error>> where
+	K: Hash + Eq + 'static,
+	V: Clone + 'static,
+	R: IntoFuture<Item=V, Error=error::Error>,
+	R::Future: 'static,
+	Fetch: FnOnce() -> R,
+{
+	if let Some(value) = cache.lock().get(&key) {
+		return Box::new(future::ok(value));
+	}
+
+	let cache = cache.clone();
+	Box::new(fetch().into_future().map(move |value| {
+		cache.lock().insert(key, value.clone());
+		value
+	}))
+}
+
+impl<K: Hash + Eq, V: Clone> Cache<K, V> {
+	fn new(capacity: usize, ttl: Duration) -> Self {
+		Cache {
+			entries: LinkedHashMap::new(),
+			capacity,
+			ttl,
+		}
+	}
+
+	fn get(&mut self, key: &K) -> Option<V> {
+		let expired = match self.entries.get_refresh(key) {
+			Some(entry) => {
+				if entry.0.elapsed() < self.ttl {
+					return Some(entry.1.clone());
+				}
+				true
+			},
+			None => false,
+		};
+
+		if expired {
+			self.entries.remove(key);
+		}
+		None
+	}
+
+	fn insert(&mut self, key: K, value: V) {
+		if self.capacity == 0 {
+			return;
+		}
+
+		self.entries.insert(key, (Instant::now(), value));
+		while self.entries.len() > self.capacity {
+			self.entries.pop_front();
+		}
+	}
+}
+
+/// Fetcher failing requests of the inner fetcher with `RemoteFetchTimeout` when they don't
+/// complete in time. A timed out request is issued again as long as its `RetryPolicy` allows.
+pub struct TimeoutFetcher<F> {
+	inner: Arc<F>,
+	timer: Timer,
+	call_timeout: Duration,
+	read_timeout: Duration,
+	header_timeout: Duration,
+}
+
+impl<F> TimeoutFetcher<F> {
+	/// Create a new timeout fetcher, giving every request `timeout` to complete.
+	pub fn new(inner: F, timeout: Duration) -> Self {
+		TimeoutFetcher {
+			inner: Arc::new(inner),
+			timer: Timer::default(),
+			call_timeout: timeout,
+			read_timeout: timeout,
+			header_timeout: timeout,
+		}
+	}
+
+	/// Give remote calls `timeout` to complete.
+	pub fn with_call_timeout(mut self, timeout: Duration) -> Self {
+		self.call_timeout = timeout;
+		self
+	}
+
+	/// Give remote storage reads, batched or not, `timeout` to complete.
+	pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+		self.read_timeout = timeout;
+		self
+	}
+
+	/// Give remote header and CHT segment requests `timeout` to complete.
+	pub fn with_header_timeout(mut self, timeout: Duration) -> Self {
+		self.header_timeout = timeout;
+		self
+	}
+}
+
+impl<B, F> Fetcher<B> for TimeoutFetcher<F> where
+	B: BlockT,
+	F: Fetcher<B> + 'static,
+	<F::RemoteCallResult as IntoFuture>::Future: 'static,
+	<F::RemoteReadResult as IntoFuture>::Future: 'static,
+	<F::RemoteBatchReadResult as IntoFuture>::Future: 'static,
+	<F::RemoteHeaderResult as IntoFuture>::Future: 'static,
+	<F::RemoteChtResult as IntoFuture>::Future: 'static,
+{
+	type RemoteCallResult = Box<Future<Item=CallResult, Error=error::Error>>;
+	type RemoteReadResult = Box<Future<Item=Option<Vec<u8>>, Error=error::Error>>;
+	type RemoteBatchReadResult = Box<Future<Item=Vec<Option<Vec<u8>>>, Error=error::Error>>;
+	type RemoteHeaderResult = Box<Future<Item=B::Header, Error=error::Error>>;
+	type RemoteChtResult = Box<Future<Item=Vec<B::Hash>, Error=error::Error>>;
+
+	fn remote_call(&self, request: RemoteCallRequest<B::Hash>) -> Self::RemoteCallResult {
+		let inner = self.inner.clone();
+		let retry = request.retry;
+		with_timeout(self.timer.clone(), self.call_timeout, retry, 0, move || inner.remote_call(request.clone()))
+	}
+
+	fn remote_read(&self, request: RemoteReadRequest<B::Hash>) -> Self::RemoteReadResult {
+		let inner = self.inner.clone();
+		let retry = request.retry;
+		with_timeout(self.timer.clone(), self.read_timeout, retry, 0, move || inner.remote_read(request.clone()))
+	}
+
+	fn remote_read_batch(&self, request: RemoteBatchReadRequest<B::Hash>) -> Self::RemoteBatchReadResult {
+		let inner = self.inner.clone();
+		let retry = request.retry;
+		with_timeout(self.timer.clone(), self.read_timeout, retry, 0, move || inner.remote_read_batch(request.clone()))
+	}
+
+	fn remote_header_by_hash(&self, request: RemoteHeaderByHashRequest<B::Hash>) -> Self::RemoteHeaderResult {
+		let inner = self.inner.clone();
+		let retry = request.retry;
+		with_timeout(self.timer.clone(), self.header_timeout, retry, 0, move || inner.remote_header_by_hash(request.clone()))
+	}
+
+	fn remote_cht(&self, request: RemoteChtRequest) -> Self::RemoteChtResult {
+		let inner = self.inner.clone();
+		let retry = request.retry;
+		with_timeout(self.timer.clone(), self.header_timeout, retry, 0, move || inner.remote_cht(request.clone()))
+	}
+}
+
+/// Race `fetch` against `timeout`, fetching again after the delay of `retry` while it times out.
+/// `attempt` is the zero-based number of the retry.
+fn with_timeout<T, R, Fetch>(
+	timer: Timer,
+	timeout: Duration,
+	retry: Option<RetryPolicy>,
+	attempt: usize,
+	fetch: Fetch,
+) -> Box<Future<Item=T, Error=error::Error>> where
+	T: 'static,
+	R: IntoFuture<Item=T, Error=error::Error>,
+	R::Future: 'static,
+	Fetch: Fn() -> R + 'static,
+{
+	let expiry = timer.sleep(timeout).then(|result| Err(match result {
+		Ok(()) => error::Error::from(error::ErrorKind::RemoteFetchTimeout),
+		Err(e) => timer_error(e),
+	}));
+	let response = fetch().into_future()
+		.select(expiry)
+		.map(|(value, _)| value)
+		.map_err(|(e, _)| e);
+
+	Box::new(response.or_else(move |e: error::Error| {
+		let delay = match *e.kind() {
+			error::ErrorKind::RemoteFetchTimeout => retry.and_then(|retry| retry.delay(attempt)),
+			_ => None,
+		};
+		match delay {
+			Some(delay) => Either::A(timer.sleep(delay)
+				.map_err(timer_error)
+				.and_then(move |_| with_timeout(timer, timeout, retry, attempt + 1, fetch))),
+			None => Either::B(future::err(e)),
+		}
+	}))
+}
+
+fn timer_error(e: TimerError) -> error::Error {
+	error::ErrorKind::Backend(format!("fetch timer failed: {:?}", e)).into()
+}
+
+/// Kind of a request sent to a `Fetcher`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FetchKind {
+	/// Remote call.
+	Call,
+	/// Remote storage read.
+	Read,
+	/// Remote storage batch read.
+	BatchRead,
+	/// Remote header request.
+	Header,
+	/// Remote CHT segment request.
+	Cht,
+}
+
+/// Receiver of the measurements of an `InstrumentedFetcher`. All callbacks do nothing by default,
+/// and `()` ignores every measurement.
+pub trait FetchMetrics: Send + Sync {
+	/// A request of given kind is sent.
+	fn on_request(&self, _kind: FetchKind) {}
+	/// A request of given kind has succeeded, `elapsed` after it was sent.
+	fn on_success(&self, _kind: FetchKind, _elapsed: Duration) {}
+	/// A request of given kind has failed.
+	fn on_failure(&self, _kind: FetchKind) {}
+}
+
+impl FetchMetrics for () {}
+
+/// Fetcher reporting every request of the inner fetcher, and its outcome, to `FetchMetrics`.
+pub struct InstrumentedFetcher<F, M> {
+	inner: F,
+	metrics: Arc<M>,
+}
+
+impl<F, M: FetchMetrics> InstrumentedFetcher<F, M> {
+	/// Create a new instrumented fetcher. `metrics` may be shared with other fetchers.
+	pub fn new(inner: F, metrics: Arc<M>) -> Self {
+		InstrumentedFetcher { inner, metrics }
+	}
+}
+
+impl<B, F, M> Fetcher<B> for InstrumentedFetcher<F, M> where
+	B: BlockT,
+	F: Fetcher<B>,
+	M: FetchMetrics + 'static,
+	<F::RemoteCallResult as IntoFuture>::Future: 'static,
+	<F::RemoteReadResult as IntoFuture>::Future: 'static,
+	<F::RemoteBatchReadResult as IntoFuture>::Future: 'static,
+	<F::RemoteHeaderResult as IntoFuture>::Future: 'static,
+	<F::RemoteChtResult as IntoFuture>::Future: 'static,
+{
+	type RemoteCallResult = Box<Future<Item=CallResult, Error=error::Error>>;
+	type RemoteReadResult = Box<Future<Item=Option<Vec<u8>>, Error=error::Error>>;
+	type RemoteBatchReadResult = Box<Future<Item=Vec<Option<Vec<u8>>>, Error=error::Error>>;
+	type RemoteHeaderResult = Box<Future<Item=B::Header, Error=error::Error>>;
+	type RemoteChtResult = Box<Future<Item=Vec<B::Hash>, Error=error::Error>>;
+
+	fn remote_call(&self, request: RemoteCallRequest<B::Hash>) -> Self::RemoteCallResult {
+		instrumented(&self.metrics, FetchKind::Call, || self.inner.remote_call(request))
+	}
+
+	fn remote_read(&self, request: RemoteReadRequest<B::Hash>) -> Self::RemoteReadResult {
+		instrumented(&self.metrics, FetchKind::Read, || self.inner.remote_read(request))
+	}
+
+	fn remote_read_batch(&self, request: RemoteBatchReadRequest<B::Hash>) -> Self::RemoteBatchReadResult {
+		instrumented(&self.metrics, FetchKind::BatchRead, || self.inner.remote_read_batch(request))
+	}
+
+	fn remote_header_by_hash(&self, request: RemoteHeaderByHashRequest<B::Hash>) -> Self::RemoteHeaderResult {
+		instrumented(&self.metrics, FetchKind::Header, || self.inner.remote_header_by_hash(request))
+	}
+
+	fn remote_cht(&self, request: RemoteChtRequest) -> Self::RemoteChtResult {
+		instrumented(&self.metrics, FetchKind::Cht, || self.inner.remote_cht(request))
+	}
+}
+
+/// Report the request issued by `fetch`, and its outcome once it completes, to `metrics`.
+fn instrumented<M, T, R, Fetch>(metrics: &Arc<M>, kind: FetchKind, fetch: Fetch) -> Box<Future<Item=T, Error=error::Error>> where
+	M: FetchMetrics + 'static,
+	T: 'static,
+	R: IntoFuture<Item=T, Error=error::Error>,
+	R::Future: 'static,
+	Fetch: FnOnce() -> R,
+{
+	metrics.on_request(kind);
+	let metrics = metrics.clone();
+	let sent = Instant::now();
+	Box::new(fetch().into_future().then(move |result| {
+		match result {
+			Ok(_) => metrics.on_success(kind, sent.elapsed()),
+			Err(_) => metrics.on_failure(kind),
+		}
+		result
+	}))
+}
+
+/// Fetcher sending every request to all inner fetchers, e.g. one per connected full node, and
+/// resolving with the first successful response. Fetchers only return data which has passed the
+/// proof check, so a node responding with an invalid proof fails only its own request. The race
+/// fails when every fetcher does, with the error of the last one to fail.
+pub struct RacingFetcher<F> {
+	inner: Vec<F>,
+}
+
+impl<F> RacingFetcher<F> {
+	/// Create a new racing fetcher.
+	pub fn new(inner: Vec<F>) -> Self {
+		RacingFetcher { inner }
+	}
+}
+
+impl<B, F> Fetcher<B> for RacingFetcher<F> where
+	B: BlockT,
+	F: Fetcher<B>,
+	<F::RemoteCallResult as IntoFuture>::Future: 'static,
+	<F::RemoteReadResult as IntoFuture>::Future: 'static,
+	<F::RemoteBatchReadResult as IntoFuture>::Future: 'static,
+	<F::RemoteHeaderResult as IntoFuture>::Future: 'static,
+	<F::RemoteChtResult as IntoFuture>::Future: 'static,
+{
+	type RemoteCallResult = Box<Future<Item=CallResult, Error=error::Error>>;
+	type RemoteReadResult = Box<Future<Item=Option<Vec<u8>>, Error=error::Error>>;
+	type RemoteBatchReadResult = Box<Future<Item=Vec<Option<Vec<u8>>>, Error=error::Error>>;
+	type RemoteHeaderResult = Box<Future<Item=B::Header, Error=error::Error>>;
+	type RemoteChtResult = Box<Future<Item=Vec<B::Hash>, Error=error::Error>>;
+
+	fn remote_call(&self, request: RemoteCallRequest<B::Hash>) -> Self::RemoteCallResult {
+		race(&self.inner, |fetcher| fetcher.remote_call(request.clone()))
+	}
+
+	fn remote_read(&self, request: RemoteReadRequest<B::Hash>) -> Self::RemoteReadResult {
+		race(&self.inner, |fetcher| fetcher.remote_read(request.clone()))
+	}
+
+	fn remote_read_batch(&self, request: RemoteBatchReadRequest<B::Hash>) -> Self::RemoteBatchReadResult {
+		race(&self.inner, |fetcher| fetcher.remote_read_batch(request.clone()))
+	}
+
+	fn remote_header_by_hash(&self, request: RemoteHeaderByHashRequest<B::Hash>) -> Self::RemoteHeaderResult {
+		race(&self.inner, |fetcher| fetcher.remote_header_by_hash(request.clone()))
+	}
+
+	fn remote_cht(&self, request: RemoteChtRequest) -> Self::RemoteChtResult {
+		race(&self.inner, |fetcher| fetcher.remote_cht(request.clone()))
+	}
+}
+
+/// Issue `fetch` to all `fetchers`, resolving with the first successful response.
+fn race<F, T, R, Fetch>(fetchers: &[F], fetch: Fetch) -> Box<Future<Item=T, Error=error::Error>> where
+	T: 'static,
+	R: IntoFuture<Item=T, Error=error::Error>,
+	R::Future: 'static,
+	Fetch: Fn(&F) -> R,
+{
+	if fetchers.is_empty() {
+		return Box::new(future::err(error::ErrorKind::Backend("no fetchers to race".into()).into()));
+	}
+
+	Box::new(future::select_ok(fetchers.iter().map(|fetcher| fetch(fetcher).into_future()))
+		.map(|(value, _)| value))
+}
+
+/// Fetchers for testing code depending on a `Fetcher`.
+#[cfg(test)]
+pub mod testing {
+	use std::collections::HashMap;
+	use std::sync::Arc;
+	use futures::future;
+	use parking_lot::Mutex;
+	use runtime_primitives::traits::{Block as BlockT, Header as HeaderT};
+	use call_executor::CallResult;
+	use error;
+	use light::{Fetcher, RemoteBatchReadRequest, RemoteCallRequest, RemoteChtRequest, RemoteHeaderByHashRequest, RemoteReadRequest};
+
+	/// In-memory fetcher for tests, serving pre-seeded call results, storage values, headers and CHT
+	/// segments without any proofs. Requests which have not been seeded fail with `UnknownBlock`, or
+	/// `UnknownChtSegment` for CHT segments. Clones share the seeded data and the request count.
+	pub struct TestFetcher<B: BlockT> {
+		calls: Arc<Mutex<HashMap<(B::Hash, String, Vec<u8>), Vec<u8>>>>,
+		reads: Arc<Mutex<HashMap<(B::Hash, Vec<u8>), Option<Vec<u8>>>>>,
+		headers: Arc<Mutex<HashMap<B::Hash, B::Header>>>,
+		chts: Arc<Mutex<HashMap<u64, Vec<B::Hash>>>>,
+		requests: Arc<Mutex<usize>>,
+	}
+
+	impl<B: BlockT> Default for TestFetcher<B> {
+		fn default() -> Self {
+			TestFetcher {
+				calls: Default::default(),
+				reads: Default::default(),
+				headers: Default::default(),
+				chts: Default::default(),
+				requests: Default::default(),
+			}
+		}
+	}
+
+	impl<B: BlockT> Clone for TestFetcher<B> {
+		fn clone(&self) -> Self {
+			TestFetcher {
+				calls: self.calls.clone(),
+				reads: self.reads.clone(),
+				headers: self.headers.clone(),
+				chts: self.chts.clone(),
+				requests: self.requests.clone(),
+			}
+		}
+	}
+
+	impl<B: BlockT> TestFetcher<B> {
+		/// Seed the data returned by a remote call.
+		pub fn insert_call(&self, request: &RemoteCallRequest<B::Hash>, return_data: Vec<u8>) {
+			self.calls.lock().insert((request.block, request.method.clone(), request.call_data.clone()), return_data);
+		}
+
+		/// Seed the value returned by a remote storage read, also serving it to batch reads.
+		pub fn insert_read(&self, request: &RemoteReadRequest<B::Hash>, value: Option<Vec<u8>>) {
+			self.reads.lock().insert((request.block, request.key.clone()), value);
+		}
+
+		/// Seed a header, served to requests for its hash.
+		pub fn insert_header(&self, header: B::Header) {
+			self.headers.lock().insert(header.hash(), header);
+		}
+
+		/// Seed the block hashes of a CHT segment.
+		pub fn insert_cht(&self, segment: u64, hashes: Vec<B::Hash>) {
+			self.chts.lock().insert(segment, hashes);
+		}
+
+		/// Number of requests the fetcher has been sent, seeded or not.
+		pub fn requests(&self) -> usize {
+			*self.requests.lock()
+		}
+
+		fn read(&self, block: B::Hash, key: &[u8]) -> error::Result<Option<Vec<u8>>> {
+			self.reads.lock()
+				.get(&(block, key.to_vec()))
+				.cloned()
+				.ok_or_else(|| error::ErrorKind::UnknownBlock(format!("{}", block)).into())
+		}
+	}
+
+	impl<B: BlockT> Fetcher<B> for TestFetcher<B> {
+		type RemoteCallResult = future::FutureResult<CallResult, error::Error>;
+		type RemoteReadResult = future::FutureResult<Option<Vec<u8>>, error::Error>;
+		type RemoteBatchReadResult = future::FutureResult<Vec<Option<Vec<u8>>>, error::Error>;
+		type RemoteHeaderResult = future::FutureResult<B::Header, error::Error>;
+		type RemoteChtResult = future::FutureResult<Vec<B::Hash>, error::Error>;
+
+		fn remote_call(&self, request: RemoteCallRequest<B::Hash>) -> Self::RemoteCallResult {
+			*self.requests.lock() += 1;
+			let key = (request.block, request.method, request.call_data);
+			future::result(self.calls.lock()
+				.get(&key)
+				.map(|return_data| CallResult { return_data: return_data.clone(), changes: Default::default() })
+				.ok_or_else(|| error::ErrorKind::UnknownBlock(format!("{}", key.0)).into()))
+		}
+
+		fn remote_read(&self, request: RemoteReadRequest<B::Hash>) -> Self::RemoteReadResult {
+			*self.requests.lock() += 1;
+			future::result(self.read(request.block, &request.key))
+		}
+
+		fn remote_read_batch(&self, request: RemoteBatchReadRequest<B::Hash>) -> Self::RemoteBatchReadResult {
+			*self.requests.lock() += 1;
+			future::result(request.keys.iter().map(|key| self.read(request.block, key)).collect())
+		}
+
+		fn remote_header_by_hash(&self, request: RemoteHeaderByHashRequest<B::Hash>) -> Self::RemoteHeaderResult {
+			*self.requests.lock() += 1;
+			future::result(self.headers.lock()
+				.get(&request.hash)
+				.cloned()
+				.ok_or_else(|| error::ErrorKind::UnknownBlock(format!("{}", request.hash)).into()))
+		}
+
+		fn remote_cht(&self, request: RemoteChtRequest) -> Self::RemoteChtResult {
+			*self.requests.lock() += 1;
+			future::result(self.chts.lock()
+				.get(&request.segment)
+				.cloned()
+				.ok_or_else(|| error::ErrorKind::UnknownChtSegment(request.segment).into()))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use std::sync::Arc;
+	use std::time::Duration;
+	use futures::{future, Future};
+	use parking_lot::Mutex;
+	use runtime_primitives::generic::BlockId;
+	use runtime_primitives::traits::Header as HeaderT;
+	use state_machine::{Backend as StateBackend, backend::InMemory};
+	use test_client::{self, runtime::{Block, Hash, Header}};
+	use backend::{Backend as ClientBackend, BlockImportOperation};
+	use call_executor::CallResult;
+	use error;
+	use light::{new_fetch_checker, new_light_backend, prove_read, FetchChecker, Fetcher, LightDataChecker,
+		RemoteBatchReadRequest, RemoteCallRequest, RemoteChtRequest, RemoteHeaderByHashRequest, RemoteReadRequest, RetryPolicy};
+	use super::{CachingFetcher, FetchKind, FetchMetrics, InstrumentedFetcher, RacingFetcher, TimeoutFetcher};
+	use super::testing::TestFetcher;
+
+	const KEYS: &[&[u8]] = &[b"key", b"other", b"first", b"second", b"third"];
+
+	fn read_request(key: &[u8]) -> RemoteReadRequest<Hash> {
+		RemoteReadRequest { block: Default::default(), key: key.to_vec(), max_proof_size: None, retry: None }
+	}
+
+	fn call_request() -> RemoteCallRequest<Hash> {
+		RemoteCallRequest {
+			block: Default::default(),
+			method: "authorities".into(),
+			call_data: vec![42],
+			expected_result: None,
+			retry: None,
+		}
+	}
+
+	fn header() -> Header {
+		HeaderT::new(1, Default::default(), Default::default(), Default::default(), Default::default())
+	}
+
+	/// Fetcher answering reads of `KEYS` with the key itself, the `call_request` with its call data,
+	/// and requests for `header()` and CHT segments 1 and 2.
+	fn seeded_fetcher() -> TestFetcher<Block> {
+		let fetcher = TestFetcher::default();
+		for key in KEYS {
+			fetcher.insert_read(&read_request(key), Some(key.to_vec()));
+		}
+		fetcher.insert_call(&call_request(), vec![42]);
+		fetcher.insert_header(header());
+		fetcher.insert_cht(1, vec![[1u8; 32].into()]);
+		fetcher.insert_cht(2, vec![[2u8; 32].into()]);
+		fetcher
+	}
+
+	fn read(fetcher: &CachingFetcher<Block, TestFetcher<Block>>, key: &[u8]) -> Option<Vec<u8>> {
+		fetcher.remote_read(read_request(key)).wait().unwrap()
+	}
+
+	fn call(fetcher: &CachingFetcher<Block, TestFetcher<Block>>) -> Vec<u8> {
+		fetcher.remote_call(call_request()).wait().unwrap().return_data
+	}
+
+	#[test]
+	fn repeated_read_is_served_from_cache() {
+		let inner = seeded_fetcher();
+		let fetcher = CachingFetcher::new(inner.clone(), 16, Duration::from_secs(60));
+		assert_eq!(read(&fetcher, b"key"), Some(b"key".to_vec()));
+		assert_eq!(read(&fetcher, b"key"), Some(b"key".to_vec()));
+		assert_eq!(inner.requests(), 1);
+
+		assert_eq!(read(&fetcher, b"other"), Some(b"other".to_vec()));
+		assert_eq!(inner.requests(), 2);
+
+		let header_request = || RemoteHeaderByHashRequest { hash: header().hash(), retry: None };
+		let header = fetcher.remote_header_by_hash(header_request()).wait().unwrap();
+		assert_eq!(fetcher.remote_header_by_hash(header_request()).wait().unwrap(), header);
+		assert_eq!(inner.requests(), 3);
+
+		let cht_request = |segment| RemoteChtRequest { segment, retry: None };
+		let hashes = fetcher.remote_cht(cht_request(1)).wait().unwrap();
+		assert_eq!(fetcher.remote_cht(cht_request(1)).wait().unwrap(), hashes);
+		assert_eq!(inner.requests(), 4);
+		assert!(fetcher.remote_cht(cht_request(2)).wait().unwrap() != hashes);
+		assert_eq!(inner.requests(), 5);
+	}
+
+	#[test]
+	fn least_recently_used_read_is_evicted() {
+		let inner = seeded_fetcher();
+		let fetcher = CachingFetcher::new(inner.clone(), 2, Duration::from_secs(60));
+		read(&fetcher, b"first");
+		read(&fetcher, b"second");
+		read(&fetcher, b"first");
+		read(&fetcher, b"third");
+		assert_eq!(inner.requests(), 3);
+
+		// `second` has been evicted, `first` was used more recently.
+		read(&fetcher, b"first");
+		assert_eq!(inner.requests(), 3);
+		read(&fetcher, b"second");
+		assert_eq!(inner.requests(), 4);
+	}
+
+	#[test]
+	fn expired_read_is_fetched_again() {
+		let inner = seeded_fetcher();
+		let fetcher = CachingFetcher::new(inner.clone(), 16, Duration::from_secs(0));
+		read(&fetcher, b"key");
+		read(&fetcher, b"key");
+		assert_eq!(inner.requests(), 2);
+	}
+
+	#[test]
+	fn calls_are_cached_only_when_enabled() {
+		let inner = seeded_fetcher();
+		let fetcher = CachingFetcher::new(inner.clone(), 16, Duration::from_secs(60));
+		assert_eq!(call(&fetcher), vec![42]);
+		assert_eq!(call(&fetcher), vec![42]);
+		assert_eq!(inner.requests(), 2);
+
+		let fetcher = fetcher.with_call_caching();
+		call(&fetcher);
+		call(&fetcher);
+		assert_eq!(inner.requests(), 3);
+	}
+
+	#[test]
+	fn test_fetcher_serves_seeded_requests() {
+		let fetcher = seeded_fetcher();
+		assert_eq!(fetcher.remote_read(read_request(b"key")).wait().unwrap(), Some(b"key".to_vec()));
+		assert_eq!(fetcher.remote_read_batch(RemoteBatchReadRequest {
+			block: Default::default(),
+			keys: vec![b"first".to_vec(), b"second".to_vec()],
+			retry: None,
+		}).wait().unwrap(), vec![Some(b"first".to_vec()), Some(b"second".to_vec())]);
+		assert_eq!(fetcher.remote_call(call_request()).wait().unwrap().return_data, vec![42]);
+		assert_eq!(fetcher.remote_header_by_hash(RemoteHeaderByHashRequest { hash: header().hash(), retry: None }).wait().unwrap(), header());
+		let hashes: Vec<Hash> = vec![[1u8; 32].into()];
+		assert_eq!(fetcher.remote_cht(RemoteChtRequest { segment: 1, retry: None }).wait().unwrap(), hashes);
+		assert_eq!(fetcher.requests(), 5);
+	}
+
+	#[test]
+	fn test_fetcher_fails_unseeded_requests() {
+		let fetcher = seeded_fetcher();
+		let is_unknown_block = |error: error::Error| match *error.kind() {
+			error::ErrorKind::UnknownBlock(_) => true,
+			_ => false,
+		};
+		assert!(is_unknown_block(fetcher.remote_read(read_request(b"missing")).wait().unwrap_err()));
+		assert!(is_unknown_block(fetcher.remote_read_batch(RemoteBatchReadRequest {
+			block: Default::default(),
+			keys: vec![b"key".to_vec(), b"missing".to_vec()],
+			retry: None,
+		}).wait().unwrap_err()));
+		assert!(is_unknown_block(fetcher.remote_header_by_hash(RemoteHeaderByHashRequest { hash: Default::default(), retry: None }).wait().unwrap_err()));
+		match *fetcher.remote_cht(RemoteChtRequest { segment: 3, retry: None }).wait().unwrap_err().kind() {
+			error::ErrorKind::UnknownChtSegment(segment) => assert_eq!(segment, 3),
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+	}
+
+	/// Fetcher never answering any request, counting the requests it has been sent. Clones share
+	/// the count.
+	#[derive(Default, Clone)]
+	struct HungFetcher {
+		sent: Arc<Mutex<usize>>,
+	}
+
+	impl HungFetcher {
+		fn hang<T>(&self) -> future::Empty<T, error::Error> {
+			*self.sent.lock() += 1;
+			future::empty()
+		}
+	}
+
+	impl Fetcher<Block> for HungFetcher {
+		type RemoteCallResult = future::Empty<CallResult, error::Error>;
+		type RemoteReadResult = future::Empty<Option<Vec<u8>>, error::Error>;
+		type RemoteBatchReadResult = future::Empty<Vec<Option<Vec<u8>>>, error::Error>;
+		type RemoteHeaderResult = future::Empty<Header, error::Error>;
+		type RemoteChtResult = future::Empty<Vec<Hash>, error::Error>;
+
+		fn remote_call(&self, _request: RemoteCallRequest<Hash>) -> Self::RemoteCallResult {
+			self.hang()
+		}
+
+		fn remote_read(&self, _request: RemoteReadRequest<Hash>) -> Self::RemoteReadResult {
+			self.hang()
+		}
+
+		fn remote_read_batch(&self, _request: RemoteBatchReadRequest<Hash>) -> Self::RemoteBatchReadResult {
+			self.hang()
+		}
+
+		fn remote_header_by_hash(&self, _request: RemoteHeaderByHashRequest<Hash>) -> Self::RemoteHeaderResult {
+			self.hang()
+		}
+
+		fn remote_cht(&self, _request: RemoteChtRequest) -> Self::RemoteChtResult {
+			self.hang()
+		}
+	}
+
+	fn is_timeout(result: Result<Option<Vec<u8>>, error::Error>) -> bool {
+		match result {
+			Err(ref e) => match *e.kind() {
+				error::ErrorKind::RemoteFetchTimeout => true,
+				_ => false,
+			},
+			Ok(_) => false,
+		}
+	}
+
+	#[test]
+	fn hung_request_times_out() {
+		let inner = HungFetcher::default();
+		let fetcher = TimeoutFetcher::new(inner.clone(), Duration::from_millis(200));
+		let result = fetcher.remote_read(read_request(b"key")).wait();
+		assert!(is_timeout(result));
+		assert_eq!(*inner.sent.lock(), 1);
+	}
+
+	#[test]
+	fn timed_out_request_is_retried() {
+		let inner = HungFetcher::default();
+		let fetcher = TimeoutFetcher::new(inner.clone(), Duration::from_secs(60)).with_read_timeout(Duration::from_millis(200));
+		let result = fetcher.remote_read(RemoteReadRequest {
+			block: Default::default(),
+			key: b"key".to_vec(),
+			max_proof_size: None,
+			retry: Some(RetryPolicy { count: 2, initial_delay_ms: 10, backoff_factor: 1 }),
+		}).wait();
+		assert!(is_timeout(result));
+		assert_eq!(*inner.sent.lock(), 3);
+	}
+
+	#[test]
+	fn timely_response_is_passed_through() {
+		let fetcher = TimeoutFetcher::new(seeded_fetcher(), Duration::from_secs(60));
+		let result = fetcher.remote_read(read_request(b"key")).wait();
+		assert_eq!(result.unwrap(), Some(b"key".to_vec()));
+	}
+
+	/// Metrics recording every measurement.
+	#[derive(Default)]
+	struct CountingMetrics {
+		events: Mutex<Vec<(FetchKind, &'static str)>>,
+	}
+
+	impl CountingMetrics {
+		fn count(&self, kind: FetchKind, event: &'static str) -> usize {
+			self.events.lock().iter().filter(|&&e| e == (kind, event)).count()
+		}
+	}
+
+	impl FetchMetrics for CountingMetrics {
+		fn on_request(&self, kind: FetchKind) {
+			self.events.lock().push((kind, "request"));
+		}
+
+		fn on_success(&self, kind: FetchKind, _elapsed: Duration) {
+			self.events.lock().push((kind, "success"));
+		}
+
+		fn on_failure(&self, kind: FetchKind) {
+			self.events.lock().push((kind, "failure"));
+		}
+	}
+
+	#[test]
+	fn metrics_count_successes_and_failures() {
+		let metrics = Arc::new(CountingMetrics::default());
+		let answering = InstrumentedFetcher::new(seeded_fetcher(), metrics.clone());
+		let hung = InstrumentedFetcher::new(TimeoutFetcher::new(HungFetcher::default(), Duration::from_millis(50)), metrics.clone());
+
+		assert!(answering.remote_read(read_request(b"key")).wait().is_ok());
+		assert!(answering.remote_read(read_request(b"key")).wait().is_ok());
+		assert!(hung.remote_read(read_request(b"key")).wait().is_err());
+		assert!(answering.remote_header_by_hash(RemoteHeaderByHashRequest { hash: header().hash(), retry: None }).wait().is_ok());
+		assert!(hung.remote_header_by_hash(RemoteHeaderByHashRequest { hash: Default::default(), retry: None }).wait().is_err());
+
+		assert_eq!(metrics.count(FetchKind::Read, "request"), 3);
+		assert_eq!(metrics.count(FetchKind::Read, "success"), 2);
+		assert_eq!(metrics.count(FetchKind::Read, "failure"), 1);
+		assert_eq!(metrics.count(FetchKind::Header, "request"), 2);
+		assert_eq!(metrics.count(FetchKind::Header, "success"), 1);
+		assert_eq!(metrics.count(FetchKind::Header, "failure"), 1);
+		assert_eq!(metrics.count(FetchKind::Call, "request"), 0);
+	}
+
+	#[test]
+	fn unit_metrics_pass_results_through() {
+		let fetcher = InstrumentedFetcher::new(seeded_fetcher(), Arc::new(()));
+		let result = fetcher.remote_read(read_request(b"key")).wait();
+		assert_eq!(result.unwrap(), Some(b"key".to_vec()));
+	}
+
+	/// Fetcher answering storage reads with a fixed proof, returning the value only if the proof
+	/// passes the check. Any other request fails.
+	struct ProvingFetcher {
+		checker: Arc<LightDataChecker<test_client::NativeExecutor, Block>>,
+		proof: Vec<Vec<u8>>,
+	}
+
+	impl Fetcher<Block> for ProvingFetcher {
+		type RemoteCallResult = future::FutureResult<CallResult, error::Error>;
+		type RemoteReadResult = future::FutureResult<Option<Vec<u8>>, error::Error>;
+		type RemoteBatchReadResult = future::FutureResult<Vec<Option<Vec<u8>>>, error::Error>;
+		type RemoteHeaderResult = future::FutureResult<Header, error::Error>;
+		type RemoteChtResult = future::FutureResult<Vec<Hash>, error::Error>;
+
+		fn remote_call(&self, _request: RemoteCallRequest<Hash>) -> Self::RemoteCallResult {
+			future::err(error::ErrorKind::Backend("not served".into()).into())
+		}
+
+		fn remote_read(&self, request: RemoteReadRequest<Hash>) -> Self::RemoteReadResult {
+			future::result(self.checker.check_read_proof(&request, self.proof.clone()))
+		}
+
+		fn remote_read_batch(&self, _request: RemoteBatchReadRequest<Hash>) -> Self::RemoteBatchReadResult {
+			future::err(error::ErrorKind::Backend("not served".into()).into())
+		}
+
+		fn remote_header_by_hash(&self, _request: RemoteHeaderByHashRequest<Hash>) -> Self::RemoteHeaderResult {
+			future::err(error::ErrorKind::Backend("not served".into()).into())
+		}
+
+		fn remote_cht(&self, _request: RemoteChtRequest) -> Self::RemoteChtResult {
+			future::err(error::ErrorKind::Backend("not served".into()).into())
+		}
+	}
+
+	fn state_with_value(value: &[u8]) -> InMemory {
+		InMemory::from(vec![(b"key".to_vec(), value.to_vec())].into_iter().collect::<HashMap<_, _>>())
+	}
+
+	/// Read `key` through fetchers answering with `proofs`, checked against the state with the
+	/// honest value.
+	fn race_reads(proofs: Vec<Vec<Vec<u8>>>) -> Result<Option<Vec<u8>>, error::Error> {
+		let state_root = state_with_value(b"honest").storage_root(::std::iter::empty()).0;
+		let header: Header = HeaderT::new(0, Default::default(), state_root.into(), Default::default(), Default::default());
+		let backend = new_light_backend();
+		let mut op = backend.begin_operation(BlockId::Number(0)).unwrap();
+		op.set_block_data(header.clone(), None, None, true).unwrap();
+		backend.commit_operation(op).unwrap();
+		let checker = Arc::new(new_fetch_checker(backend, test_client::NativeExecutor::new()));
+
+		let fetchers = proofs.into_iter().map(|proof| ProvingFetcher { checker: checker.clone(), proof }).collect();
+		RacingFetcher::new(fetchers).remote_read(RemoteReadRequest {
+			block: header.hash(),
+			key: b"key".to_vec(),
+			max_proof_size: None,
+			retry: None,
+		}).wait()
+	}
+
+	#[test]
+	fn honest_response_wins_race() {
+		let honest = prove_read(state_with_value(b"honest"), b"key").unwrap();
+		let lying = prove_read(state_with_value(b"lying"), b"key").unwrap();
+		assert_eq!(race_reads(vec![lying.clone(), honest.clone()]).unwrap(), Some(b"honest".to_vec()));
+		assert_eq!(race_reads(vec![honest, lying]).unwrap(), Some(b"honest".to_vec()));
+	}
+
+	#[test]
+	fn race_fails_when_all_responses_fail() {
+		let lying = prove_read(state_with_value(b"lying"), b"key").unwrap();
+		assert!(race_reads(vec![lying.clone(), lying]).is_err());
+
+		match *race_reads(vec![]).unwrap_err().kind() {
+			error::ErrorKind::Backend(_) => (),
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+	}
+}
@@ -31,13 +31,18 @@ extern crate substrate_state_machine as state_machine;
 
 extern crate ed25519;
 extern crate futures;
+extern crate linked_hash_map;
 extern crate parking_lot;
+extern crate serde;
+extern crate tokio_timer;
 extern crate triehash;
 
 #[macro_use] extern crate error_chain;
 #[macro_use] extern crate log;
+#[macro_use] extern crate serde_derive;
 #[cfg(test)] #[macro_use] extern crate substrate_executor as executor;
 #[cfg(test)] #[macro_use] extern crate hex_literal;
+#[cfg(test)] extern crate serde_json;
 
 pub mod error;
 pub mod blockchain;
@@ -46,6 +51,8 @@ pub mod in_mem;
 pub mod genesis;
 pub mod block_builder;
 pub mod light;
+pub mod cht;
+pub mod fetcher;
 mod call_executor;
 mod client;
 
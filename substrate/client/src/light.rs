@@ -17,23 +17,30 @@
 //! Light client backend. Only stores headers and justifications of blocks.
 //! Everything else is requested from full nodes on demand.
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use futures::future::IntoFuture;
+use parking_lot::RwLock;
+use primitives::AuthorityId;
 use state_machine::{CodeExecutor, TryIntoTrieBackend as TryIntoStateTrieBackend,
 	TrieBackend as StateTrieBackend};
 use state_machine::backend::Backend as StateBackend;
 use runtime_primitives::generic::BlockId;
 use runtime_primitives::bft::Justification;
-use runtime_primitives::traits::{Block as BlockT, Header as HeaderT};
+use runtime_primitives::traits::{As, Block as BlockT, Header as HeaderT, One};
 use runtime_primitives::BuildStorage;
-use blockchain::{self, BlockStatus};
-use backend;
+use blockchain::{self, BlockStatus, Backend as BlockchainBackend};
+use backend::{self, Backend as ClientBackend};
 use call_executor::{CallResult, RemoteCallExecutor, check_execution_proof};
 use client::Client;
+use codec::{Input, Slicable};
+use cht;
 use error;
 use in_mem::Blockchain as InMemBlockchain;
 
 /// Remote call request.
+#[derive(Clone)]
 pub struct RemoteCallRequest<H> {
 	/// Call at state of block referenced by given header hash.
 	pub block: H,
@@ -41,22 +48,188 @@ pub struct RemoteCallRequest<H> {
 	pub method: String,
 	/// Call data.
 	pub call_data: Vec<u8>,
+	/// Result the call is expected to return, if already known to the caller.
+	pub expected_result: Option<Vec<u8>>,
+	/// Retry policy, if different from the fetcher's default.
+	pub retry: Option<RetryPolicy>,
+}
+
+/// Remote storage read request.
+#[derive(Clone)]
+pub struct RemoteReadRequest<H> {
+	/// Read at state of block referenced by given header hash.
+	pub block: H,
+	/// Storage key to read.
+	pub key: Vec<u8>,
+	/// Maximal total size of the proof nodes, in bytes.
+	pub max_proof_size: Option<usize>,
+	/// Retry policy, if different from the fetcher's default.
+	pub retry: Option<RetryPolicy>,
+}
+
+/// Remote storage batch read request.
+#[derive(Clone)]
+pub struct RemoteBatchReadRequest<H> {
+	/// Read at state of block referenced by given header hash.
+	pub block: H,
+	/// Storage keys to read.
+	pub keys: Vec<Vec<u8>>,
+	/// Retry policy, if different from the fetcher's default.
+	pub retry: Option<RetryPolicy>,
+}
+
+/// Remote header request, by hash.
+#[derive(Clone)]
+pub struct RemoteHeaderByHashRequest<H> {
+	/// Hash of the requested header.
+	pub hash: H,
+	/// Retry policy, if different from the fetcher's default.
+	pub retry: Option<RetryPolicy>,
+}
+
+/// Remote request of the block hashes of a CHT segment.
+#[derive(Clone)]
+pub struct RemoteChtRequest {
+	/// Segment of the CHT, see `cht::segment_range`.
+	pub segment: u64,
+	/// Retry policy, if different from the fetcher's default.
+	pub retry: Option<RetryPolicy>,
+}
+
+/// How often a failed remote request is retried, and how long to wait between attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+	/// Maximal number of retries.
+	pub count: usize,
+	/// Delay before the first retry, in milliseconds.
+	pub initial_delay_ms: u64,
+	/// Factor the delay is multiplied by after every retry.
+	pub backoff_factor: u32,
+}
+
+impl RetryPolicy {
+	/// Delay before the given (zero-based) retry, or `None` if no retries are left.
+	pub fn delay(&self, retry: usize) -> Option<Duration> {
+		if retry >= self.count {
+			return None;
+		}
+
+		let mut delay_ms = self.initial_delay_ms;
+		for _ in 0..retry {
+			delay_ms = delay_ms.saturating_mul(self.backoff_factor as u64);
+		}
+		Some(Duration::from_millis(delay_ms))
+	}
+}
+
+/// Outcome of a remote read conditional on the hash of an already known value.
+#[derive(Debug, PartialEq)]
+pub enum ReadOutcome {
+	/// The value matches the known hash.
+	Unchanged,
+	/// The value, checked against the read proof, differs from the known one.
+	Changed(Option<Vec<u8>>),
+}
+
+/// Last header verified by a syncing light client, to resume the header sync from after a restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncCheckpoint<N, H> {
+	/// Number of the last verified header.
+	pub last_number: N,
+	/// Hash of the last verified header.
+	pub last_hash: H,
+}
+
+impl<N: Slicable, H: Slicable> Slicable for SyncCheckpoint<N, H> {
+	fn decode<I: Input>(input: &mut I) -> Option<Self> {
+		Some(SyncCheckpoint {
+			last_number: Slicable::decode(input)?,
+			last_hash: Slicable::decode(input)?,
+		})
+	}
+
+	fn encode(&self) -> Vec<u8> {
+		let mut v = Vec::new();
+		self.last_number.using_encoded(|s| v.extend(s));
+		self.last_hash.using_encoded(|s| v.extend(s));
+		v
+	}
 }
 
 /// Light client data fetcher. Implementations of this trait must check if remote data
-/// is correct (see FetchedDataChecker) and return already checked data.
+/// is correct (see FetchedDataChecker) and return already checked data. When a request carries
+/// a `RetryPolicy`, implementations should give up after its retry count and wait for its delay
+/// between attempts.
 pub trait Fetcher<B: BlockT>: Send + Sync {
 	/// Remote call result future.
 	type RemoteCallResult: IntoFuture<Item=CallResult, Error=error::Error>;
+	/// Remote storage read future.
+	type RemoteReadResult: IntoFuture<Item=Option<Vec<u8>>, Error=error::Error>;
+	/// Remote storage batch read future.
+	type RemoteBatchReadResult: IntoFuture<Item=Vec<Option<Vec<u8>>>, Error=error::Error>;
+	/// Remote header future.
+	type RemoteHeaderResult: IntoFuture<Item=B::Header, Error=error::Error>;
+	/// Remote CHT segment future.
+	type RemoteChtResult: IntoFuture<Item=Vec<B::Hash>, Error=error::Error>;
 
 	/// Fetch remote call result.
 	fn remote_call(&self, request: RemoteCallRequest<B::Hash>) -> Self::RemoteCallResult;
+	/// Fetch remote storage value.
+	fn remote_read(&self, request: RemoteReadRequest<B::Hash>) -> Self::RemoteReadResult;
+	/// Fetch remote storage values, in the order of requested keys.
+	fn remote_read_batch(&self, request: RemoteBatchReadRequest<B::Hash>) -> Self::RemoteBatchReadResult;
+	/// Fetch remote header with given hash.
+	fn remote_header_by_hash(&self, request: RemoteHeaderByHashRequest<B::Hash>) -> Self::RemoteHeaderResult;
+	/// Fetch hashes of all blocks of the CHT segment, in the order of block numbers.
+	fn remote_cht(&self, request: RemoteChtRequest) -> Self::RemoteChtResult;
 }
 
 /// Light client remote data checker.
 pub trait FetchChecker<B: BlockT>: Send + Sync {
 	/// Check remote method execution proof.
 	fn check_execution_proof(&self, request: &RemoteCallRequest<B::Hash>, remote_proof: Vec<Vec<u8>>) -> error::Result<CallResult>;
+	/// Check remote storage read proof.
+	fn check_read_proof(&self, request: &RemoteReadRequest<B::Hash>, remote_proof: Vec<Vec<u8>>) -> error::Result<Option<Vec<u8>>>;
+	/// Check remote storage read proof, covering all requested keys.
+	fn check_read_batch_proof(&self, request: &RemoteBatchReadRequest<B::Hash>, remote_proof: Vec<Vec<u8>>) -> error::Result<Vec<Option<Vec<u8>>>>;
+	/// Check that the CHT segment request can be checked once answered: the segment must be within
+	/// the local chain and its root must be known.
+	fn check_cht_request(&self, request: &RemoteChtRequest) -> error::Result<()>;
+	/// Check remote hashes of the CHT segment blocks. The hashes are their own proof: together they
+	/// must build the locally known root of the segment.
+	fn check_cht_proof(&self, request: &RemoteChtRequest, remote_hashes: Vec<B::Hash>) -> error::Result<Vec<B::Hash>>;
+
+	/// Check remote header, requested by hash. No proof is required, since the hash itself
+	/// commits to the header.
+	fn check_header_by_hash_proof(&self, request: &RemoteHeaderByHashRequest<B::Hash>, remote_header: Option<B::Header>) -> error::Result<B::Header> {
+		let header = remote_header.ok_or_else(|| error::ErrorKind::UnknownBlock(format!("{}", request.hash)))?;
+		if header.hash() != request.hash {
+			return Err(error::ErrorKind::InvalidRemoteHeader(format!("{}", request.hash)).into());
+		}
+
+		Ok(header)
+	}
+
+	/// Check that `claimed_child` is the direct child of the `trusted` header.
+	fn check_header_ancestry(&self, trusted: &B::Header, claimed_child: &B::Header) -> error::Result<()> {
+		if *claimed_child.parent_hash() != trusted.hash() || *claimed_child.number() != *trusted.number() + One::one() {
+			return Err(error::ErrorKind::InvalidHeaderAncestry(format!("{}", claimed_child.hash())).into());
+		}
+
+		Ok(())
+	}
+
+	/// Check that `chain` extends the `trusted` header one block at a time, failing at the first
+	/// header which does not descend from its predecessor.
+	fn check_header_chain(&self, trusted: &B::Header, chain: &[B::Header]) -> error::Result<()> {
+		let mut parent = trusted;
+		for header in chain {
+			self.check_header_ancestry(parent, header)?;
+			parent = header;
+		}
+
+		Ok(())
+	}
 }
 
 /// Light client backend.
@@ -87,6 +260,10 @@ pub struct LightDataChecker<E, B: BlockT> {
 	backend: Arc<Backend<B>>,
 	/// Executor.
 	executor: E,
+	/// Number and hash of the highest finalized block verified by this checker.
+	finalized: RwLock<Option<(<<B as BlockT>::Header as HeaderT>::Number, B::Hash)>>,
+	/// Known roots of CHT segments, by segment.
+	cht_roots: RwLock<HashMap<u64, B::Hash>>,
 }
 
 struct PendingBlock<B: BlockT> {
@@ -219,6 +396,176 @@ impl<E, B> FetchChecker<B> for LightDataChecker<E, B>
 	fn check_execution_proof(&self, request: &RemoteCallRequest<B::Hash>, remote_proof: Vec<Vec<u8>>) -> error::Result<CallResult> {
 		check_execution_proof(&*self.backend, &self.executor, request, remote_proof)
 	}
+
+	fn check_read_proof(&self, request: &RemoteReadRequest<B::Hash>, remote_proof: Vec<Vec<u8>>) -> error::Result<Option<Vec<u8>>> {
+		if let Some(max_proof_size) = request.max_proof_size {
+			let proof_size = remote_proof.iter().map(|node| node.len()).sum();
+			if proof_size > max_proof_size {
+				return Err(error::ErrorKind::ProofTooLarge(proof_size, max_proof_size).into());
+			}
+		}
+
+		let remote_proof = unversioned_proof(remote_proof)?;
+		let local_state_root = self.read_state_root(request.block)?;
+		::state_machine::read_proof_check(local_state_root.into(), remote_proof, &request.key).map_err(Into::into)
+	}
+
+	fn check_read_batch_proof(&self, request: &RemoteBatchReadRequest<B::Hash>, remote_proof: Vec<Vec<u8>>) -> error::Result<Vec<Option<Vec<u8>>>> {
+		let remote_proof = unversioned_proof(remote_proof)?;
+		let local_state_root = self.read_state_root(request.block)?;
+		::state_machine::read_batch_proof_check(local_state_root.into(), remote_proof, &request.keys).map_err(Into::into)
+	}
+
+	fn check_cht_request(&self, request: &RemoteChtRequest) -> error::Result<()> {
+		self.cht_root(request.segment).map(|_| ())
+	}
+
+	fn check_cht_proof(&self, request: &RemoteChtRequest, remote_hashes: Vec<B::Hash>) -> error::Result<Vec<B::Hash>> {
+		let local_root = self.cht_root(request.segment)?;
+
+		if remote_hashes.len() as u64 != cht::SIZE
+			|| cht::build_cht_root::<<B::Header as HeaderT>::Hashing>(&remote_hashes) != local_root
+		{
+			return Err(error::ErrorKind::InvalidChtProof(request.segment).into());
+		}
+
+		Ok(remote_hashes)
+	}
+}
+
+impl<E, B: BlockT> LightDataChecker<E, B> {
+	/// Check justification of the finalized header. The header must be above the highest
+	/// finalized block that has been accepted by this checker, so that a remote node can not
+	/// roll the light client back to an older finalized state.
+	pub fn check_finalized_header(
+		&self,
+		authorities: &[AuthorityId],
+		header: &B::Header,
+		justification: ::bft::UncheckedJustification<B::Hash>,
+	) -> error::Result<::bft::Justification<B::Hash>> {
+		let mut finalized = self.finalized.write();
+		if let Some((best, _)) = *finalized {
+			if *header.number() <= best {
+				return Err(error::ErrorKind::NonMonotonicFinality(
+					format!("{}", header.number()),
+					format!("{}", best),
+				).into());
+			}
+		}
+
+		let justification = ::bft::check_justification::<B>(authorities, header.parent_hash().clone(), justification)
+			.map_err(|_| error::ErrorKind::BadJustification(format!("{}", header.hash())))?;
+		if justification.digest != header.hash() {
+			return Err(error::ErrorKind::BadJustification(format!("{}", header.hash())).into());
+		}
+		*finalized = Some((*header.number(), header.hash()));
+		Ok(justification)
+	}
+
+	/// State root of the block that remote storage is read at.
+	fn read_state_root(&self, block: B::Hash) -> error::Result<B::Hash> {
+		let local_header = self.backend.blockchain().header(BlockId::Hash(block))?;
+		let local_header = local_header.ok_or_else(|| error::ErrorKind::UnknownBlock(format!("{}", block)))?;
+		if !self.is_on_finalized_chain(&local_header)? {
+			return Err(error::ErrorKind::NotFinalizedChain(format!("{}", block)).into());
+		}
+
+		Ok(local_header.state_root().clone())
+	}
+
+	/// Remember the root of the CHT `segment`, which remote segment hashes are checked against.
+	pub fn note_cht_root(&self, segment: u64, root: B::Hash) {
+		self.cht_roots.write().insert(segment, root);
+	}
+
+	/// Known root of the CHT `segment`, which must be within the local chain.
+	fn cht_root(&self, segment: u64) -> error::Result<B::Hash> {
+		let (_, last) = cht::segment_range(segment)
+			.ok_or_else(|| error::ErrorKind::UnknownChtSegment(segment))?;
+		let best_number: usize = self.backend.blockchain().info()?.best_number.as_();
+		if last > best_number as u64 {
+			return Err(error::ErrorKind::UnknownChtSegment(segment).into());
+		}
+
+		self.cht_roots.read().get(&segment).cloned()
+			.ok_or_else(|| error::ErrorKind::UnknownChtSegment(segment).into())
+	}
+
+	/// Number of the highest finalized block that has been accepted by this checker.
+	pub fn finalized_number(&self) -> Option<<<B as BlockT>::Header as HeaderT>::Number> {
+		self.finalized.read().map(|(number, _)| number)
+	}
+
+	/// True if the header is known to be an ancestor or a descendant of the highest finalized
+	/// block accepted by this checker. Always true if no block has been finalized yet.
+	fn is_on_finalized_chain(&self, header: &B::Header) -> error::Result<bool> {
+		let (finalized_number, finalized_hash) = match *self.finalized.read() {
+			Some(finalized) => finalized,
+			None => return Ok(true),
+		};
+
+		if *header.number() >= finalized_number {
+			self.is_known_ancestor(finalized_number, finalized_hash, header.clone())
+		} else {
+			let finalized_header = match self.backend.blockchain().header(BlockId::Hash(finalized_hash))? {
+				Some(finalized_header) => finalized_header,
+				None => return Ok(false),
+			};
+			self.is_known_ancestor(*header.number(), header.hash(), finalized_header)
+		}
+	}
+
+	/// True if the block with given number and hash is an ancestor of (or is) the `descendant`,
+	/// following parents that are known to the local blockchain.
+	fn is_known_ancestor(
+		&self,
+		number: <<B as BlockT>::Header as HeaderT>::Number,
+		hash: B::Hash,
+		mut descendant: B::Header,
+	) -> error::Result<bool> {
+		while *descendant.number() > number {
+			descendant = match self.backend.blockchain().header(BlockId::Hash(*descendant.parent_hash()))? {
+				Some(parent) => parent,
+				None => return Ok(false),
+			};
+		}
+		Ok(descendant.hash() == hash)
+	}
+}
+
+impl<E, B: BlockT> LightDataChecker<E, B> where Self: FetchChecker<B> {
+	/// Check that `chain` extends the `trusted` header, returning the checkpoint of its last header
+	/// (or of `trusted` itself, if `chain` is empty).
+	pub fn check_header_chain_checkpoint(
+		&self,
+		trusted: &B::Header,
+		chain: &[B::Header],
+	) -> error::Result<SyncCheckpoint<<<B as BlockT>::Header as HeaderT>::Number, B::Hash>> {
+		self.check_header_chain(trusted, chain)?;
+		let last = chain.last().unwrap_or(trusted);
+		Ok(SyncCheckpoint { last_number: *last.number(), last_hash: last.hash() })
+	}
+
+	/// Resume the header sync from `checkpoint`: check that `chain` starts with the child of the
+	/// checkpointed header and extends it one block at a time, returning the new checkpoint.
+	pub fn resume_header_chain(
+		&self,
+		checkpoint: &SyncCheckpoint<<<B as BlockT>::Header as HeaderT>::Number, B::Hash>,
+		chain: &[B::Header],
+	) -> error::Result<SyncCheckpoint<<<B as BlockT>::Header as HeaderT>::Number, B::Hash>> {
+		let first = match chain.first() {
+			Some(first) => first,
+			None => return Ok(checkpoint.clone()),
+		};
+		if *first.parent_hash() != checkpoint.last_hash || *first.number() != checkpoint.last_number + One::one() {
+			return Err(error::ErrorKind::CheckpointMismatch(
+				format!("{}", checkpoint.last_hash),
+				format!("{}", first.hash()),
+			).into());
+		}
+
+		self.check_header_chain_checkpoint(first, &chain[1..])
+	}
 }
 
 /// Create an instance of light client backend.
@@ -252,5 +599,471 @@ pub fn new_fetch_checker<E, Block>(
 		E: CodeExecutor,
 		Block: BlockT,
 {
-	LightDataChecker { backend, executor }
+	LightDataChecker { backend, executor, finalized: RwLock::new(None), cht_roots: RwLock::new(HashMap::new()) }
+}
+
+/// Generate the proof of `key` in `state`, as it is checked by `FetchChecker::check_read_proof`
+/// against the state root of the block the state belongs to.
+pub fn prove_read<S: TryIntoStateTrieBackend>(state: S, key: &[u8]) -> error::Result<Vec<Vec<u8>>> {
+	::state_machine::prove_read(state, key)
+		.map(|(_, proof)| versioned_proof(proof))
+		.map_err(Into::into)
+}
+
+/// Version of the format of the remote read proofs produced by this client. Proofs are framed by a
+/// leading single-byte node holding their version; unframed proofs are of the legacy version 0.
+pub const PROOF_VERSION: u8 = 1;
+
+/// Frame `proof` with the current `PROOF_VERSION`.
+pub fn versioned_proof(mut proof: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+	proof.insert(0, vec![PROOF_VERSION]);
+	proof
+}
+
+/// Strip the version frame off `proof`, failing for versions newer than `PROOF_VERSION`. Trie nodes
+/// are never a single byte below 0x80, so a legacy proof without the frame is passed through.
+fn unversioned_proof(mut proof: Vec<Vec<u8>>) -> error::Result<Vec<Vec<u8>>> {
+	let version = match proof.first() {
+		Some(node) if node.len() == 1 && node[0] < 0x80 => node[0],
+		_ => return Ok(proof),
+	};
+	if version > PROOF_VERSION {
+		return Err(error::ErrorKind::UnsupportedProofVersion(version).into());
+	}
+
+	proof.remove(0);
+	Ok(proof)
+}
+
+#[cfg(test)]
+mod tests {
+	use codec::Slicable;
+	use keyring::Keyring;
+	use runtime_primitives::generic::BlockId;
+	use runtime_primitives::traits::Header as HeaderT;
+	use std::collections::HashMap;
+	use state_machine::{Backend as StateBackend, backend::InMemory};
+	use test_client::{self, runtime::{Block, Hash, Header}};
+	use error::{Error, ErrorKind};
+	use std::time::Duration;
+	use cht;
+	use super::{new_light_backend, new_fetch_checker, prove_read, FetchChecker, PROOF_VERSION, LightDataChecker, RemoteBatchReadRequest,
+		RemoteChtRequest, RemoteHeaderByHashRequest, RemoteReadRequest, RetryPolicy, SyncCheckpoint};
+
+	fn test_checker() -> LightDataChecker<test_client::NativeExecutor, Block> {
+		new_fetch_checker(new_light_backend(), test_client::NativeExecutor::new())
+	}
+
+	fn checker_with_header(header: Header) -> LightDataChecker<test_client::NativeExecutor, Block> {
+		let backend = new_light_backend();
+		backend.blockchain.storage.insert(header.hash(), header, None, None, true);
+		new_fetch_checker(backend, test_client::NativeExecutor::new())
+	}
+
+	fn remote_genesis_header(remote_client: &test_client::client::Client<test_client::Backend, test_client::Executor, Block>) -> Header {
+		let remote_block_id = BlockId::Number(0);
+		let mut header = remote_client.header(&remote_block_id).unwrap().unwrap();
+		header.state_root = remote_client.state_at(&remote_block_id).unwrap()
+			.storage_root(::std::iter::empty()).0.into();
+		header
+	}
+
+	fn header(number: u64) -> Header {
+		HeaderT::new(number, Default::default(), Default::default(), [number as u8; 32].into(), Default::default())
+	}
+
+	fn child_of(parent: &Header) -> Header {
+		HeaderT::new(parent.number + 1, Default::default(), Default::default(), parent.hash(), Default::default())
+	}
+
+	/// Checker that knows the headers of the first CHT segment and its root, with the segment hashes.
+	fn checker_with_cht_segment() -> (LightDataChecker<test_client::NativeExecutor, Block>, Vec<Hash>) {
+		let (checker, hashes) = checker_with_cht_headers();
+		checker.note_cht_root(0, cht::build_cht_root::<<Header as HeaderT>::Hashing>(&hashes));
+		(checker, hashes)
+	}
+
+	/// Checker that knows the headers of the first CHT segment, but not its root.
+	fn checker_with_cht_headers() -> (LightDataChecker<test_client::NativeExecutor, Block>, Vec<Hash>) {
+		let checker = test_checker();
+		let mut hashes = Vec::new();
+		let mut parent = header(0);
+		for _ in 0..cht::SIZE {
+			hashes.push(parent.hash());
+			let child = child_of(&parent);
+			checker.backend.blockchain.storage.insert(parent.hash(), parent, None, None, true);
+			parent = child;
+		}
+		(checker, hashes)
+	}
+
+	fn justify(header: &Header) -> ::bft::UncheckedJustification<Hash> {
+		let hash = header.hash();
+		::bft::UncheckedJustification {
+			digest: hash,
+			signatures: [Keyring::Alice, Keyring::Bob, Keyring::Charlie].iter().map(|key| {
+				let msg = ::bft::sign_message::<Block>(
+					::bft::generic::Vote::Commit(1, hash).into(),
+					&key.pair(),
+					header.parent_hash,
+				);
+
+				match msg {
+					::bft::generic::LocalizedMessage::Vote(vote) => vote.signature,
+					_ => panic!("signing vote leads to signed vote"),
+				}
+			}).collect(),
+			round_number: 1,
+		}
+	}
+
+	fn authorities() -> Vec<[u8; 32]> {
+		vec![
+			Keyring::Alice.to_raw_public(),
+			Keyring::Bob.to_raw_public(),
+			Keyring::Charlie.to_raw_public(),
+		]
+	}
+
+	#[test]
+	fn finalized_header_is_accepted() {
+		let checker = test_checker();
+		let header = header(1);
+		assert!(checker.check_finalized_header(&authorities(), &header, justify(&header)).is_ok());
+		assert_eq!(checker.finalized_number(), Some(1));
+	}
+
+	#[test]
+	fn finalized_header_justified_for_sibling_is_rejected() {
+		let checker = test_checker();
+		let header = header(1);
+		let mut sibling = header.clone();
+		sibling.state_root = [1u8; 32].into();
+
+		match checker.check_finalized_header(&authorities(), &header, justify(&sibling)) {
+			Err(Error(ErrorKind::BadJustification(_), _)) => (),
+			_ => panic!("expected BadJustification"),
+		}
+		assert_eq!(checker.finalized_number(), None);
+		assert!(checker.check_finalized_header(&authorities(), &header, justify(&header)).is_ok());
+	}
+
+	#[test]
+	fn older_finalized_header_is_rejected() {
+		let checker = test_checker();
+		let new_header = header(10);
+		let old_header = header(5);
+		checker.check_finalized_header(&authorities(), &new_header, justify(&new_header)).unwrap();
+
+		match checker.check_finalized_header(&authorities(), &old_header, justify(&old_header)) {
+			Err(Error(ErrorKind::NonMonotonicFinality(_, _), _)) => (),
+			_ => panic!("expected NonMonotonicFinality"),
+		}
+		match checker.check_finalized_header(&authorities(), &new_header, justify(&new_header)) {
+			Err(Error(ErrorKind::NonMonotonicFinality(_, _), _)) => (),
+			_ => panic!("expected NonMonotonicFinality"),
+		}
+		assert_eq!(checker.finalized_number(), Some(10));
+	}
+
+	#[test]
+	fn storage_read_proof_is_generated_and_checked() {
+		// prepare remote client
+		let remote_client = test_client::new();
+		let remote_block_id = BlockId::Number(0);
+		let remote_block_header = remote_genesis_header(&remote_client);
+		let remote_block_hash = remote_block_header.hash();
+
+		// 'fetch' read proof from remote node
+		let authorities_len = remote_client.authorities_at(&remote_block_id).unwrap().len();
+		let remote_read_proof = remote_client.read_proof(&remote_block_id, b":auth:len").unwrap();
+
+		// check remote read proof locally
+		let local_checker = checker_with_header(remote_block_header);
+		assert_eq!(local_checker.check_read_proof(&RemoteReadRequest {
+			block: remote_block_hash,
+			key: b":auth:len".to_vec(),
+			max_proof_size: None,
+			retry: None,
+		}, remote_read_proof).unwrap().unwrap()[0], authorities_len as u8);
+	}
+
+	#[test]
+	fn proof_of_in_memory_state_is_checked() {
+		let state = InMemory::from(vec![
+			(b"key".to_vec(), b"value".to_vec()),
+			(b"other".to_vec(), b"other value".to_vec()),
+		].into_iter().collect::<HashMap<_, _>>());
+		let state_root = state.storage_root(::std::iter::empty()).0;
+		let header: Header = HeaderT::new(0, Default::default(), state_root.into(), Default::default(), Default::default());
+		let read = |key: &[u8], proof| checker_with_header(header.clone()).check_read_proof(&RemoteReadRequest {
+			block: header.hash(),
+			key: key.to_vec(),
+			max_proof_size: None,
+			retry: None,
+		}, proof);
+
+		assert_eq!(read(b"key", prove_read(state.clone(), b"key").unwrap()).unwrap(), Some(b"value".to_vec()));
+		assert_eq!(read(b"missing", prove_read(state.clone(), b"missing").unwrap()).unwrap(), None);
+		assert!(read(b"other", prove_read(state, b"key").unwrap()).is_err());
+	}
+
+	#[test]
+	fn read_proof_versions_are_checked() {
+		let state = InMemory::from(vec![(b"key".to_vec(), b"value".to_vec())].into_iter().collect::<HashMap<_, _>>());
+		let state_root = state.storage_root(::std::iter::empty()).0;
+		let header: Header = HeaderT::new(0, Default::default(), state_root.into(), Default::default(), Default::default());
+		let checker = checker_with_header(header.clone());
+		let read = |proof| checker.check_read_proof(&RemoteReadRequest {
+			block: header.hash(),
+			key: b"key".to_vec(),
+			max_proof_size: None,
+			retry: None,
+		}, proof);
+
+		let proof = prove_read(state.clone(), b"key").unwrap();
+		assert_eq!(proof[0], vec![PROOF_VERSION]);
+		assert_eq!(read(proof.clone()).unwrap(), Some(b"value".to_vec()));
+
+		// legacy proofs, unframed or explicitly of version 0.
+		let legacy_proof = ::state_machine::prove_read(state, b"key").unwrap().1;
+		assert_eq!(read(legacy_proof.clone()).unwrap(), Some(b"value".to_vec()));
+		assert_eq!(read(vec![vec![0]].into_iter().chain(legacy_proof.clone()).collect()).unwrap(), Some(b"value".to_vec()));
+
+		match *read(vec![vec![PROOF_VERSION + 1]].into_iter().chain(legacy_proof).collect()).unwrap_err().kind() {
+			ErrorKind::UnsupportedProofVersion(version) => assert_eq!(version, PROOF_VERSION + 1),
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+	}
+
+	#[test]
+	fn read_at_block_off_finalized_chain_is_rejected() {
+		let remote_client = test_client::new();
+		let genesis = remote_genesis_header(&remote_client);
+		let remote_read_proof = remote_client.read_proof(&BlockId::Number(0), b":auth:len").unwrap();
+
+		// finalized block #1, its descendant #2 and a fork block #1' - all sharing genesis state
+		let finalized: Header = HeaderT::new(1, Default::default(), Default::default(), genesis.hash(), Default::default());
+		let descendant: Header = HeaderT::new(2, Default::default(), genesis.state_root, finalized.hash(), Default::default());
+		let fork: Header = HeaderT::new(1, Default::default(), genesis.state_root, genesis.hash(), Default::default());
+
+		let checker = checker_with_header(genesis.clone());
+		for header in vec![finalized.clone(), descendant.clone(), fork.clone()] {
+			checker.backend.blockchain.storage.insert(header.hash(), header, None, None, false);
+		}
+		checker.check_finalized_header(&authorities(), &finalized, justify(&finalized)).unwrap();
+
+		let read_at = |block: Hash| checker.check_read_proof(&RemoteReadRequest {
+			block,
+			key: b":auth:len".to_vec(),
+			max_proof_size: None,
+			retry: None,
+		}, remote_read_proof.clone());
+		assert!(read_at(genesis.hash()).unwrap().is_some());
+		assert!(read_at(descendant.hash()).unwrap().is_some());
+		match read_at(fork.hash()) {
+			Err(Error(ErrorKind::NotFinalizedChain(_), _)) => (),
+			_ => panic!("expected NotFinalizedChain"),
+		}
+	}
+
+	#[test]
+	fn storage_read_batch_proof_is_generated_and_checked() {
+		// prepare remote client
+		let remote_client = test_client::new();
+		let remote_block_id = BlockId::Number(0);
+		let remote_block_header = remote_genesis_header(&remote_client);
+		let remote_block_hash = remote_block_header.hash();
+
+		// 'fetch' read proof from remote node
+		let keys = vec![b":auth:len".to_vec(), b":missing".to_vec(), b":code".to_vec()];
+		let authorities_len = remote_client.authorities_at(&remote_block_id).unwrap().len();
+		let remote_read_proof = remote_client.read_batch_proof(&remote_block_id, &keys).unwrap();
+
+		// check remote read proof locally
+		let local_checker = checker_with_header(remote_block_header);
+		let values = local_checker.check_read_batch_proof(&RemoteBatchReadRequest {
+			block: remote_block_hash,
+			keys,
+			retry: None,
+		}, remote_read_proof).unwrap();
+		assert_eq!(values.len(), 3);
+		assert_eq!(values[0].as_ref().unwrap()[0], authorities_len as u8);
+		assert_eq!(values[1], None);
+		assert!(values[2].is_some());
+	}
+
+	#[test]
+	fn header_ancestry_is_checked() {
+		let checker = test_checker();
+		let trusted = header(1);
+		let child = child_of(&trusted);
+		checker.check_header_ancestry(&trusted, &child).unwrap();
+
+		// right number, wrong parent.
+		assert!(checker.check_header_ancestry(&trusted, &header(2)).is_err());
+
+		// right parent, wrong number.
+		let mut skipping = child.clone();
+		skipping.number = 3;
+		assert!(checker.check_header_ancestry(&trusted, &skipping).is_err());
+	}
+
+	#[test]
+	fn header_chain_reports_first_break() {
+		let checker = test_checker();
+		let trusted = header(1);
+		let first = child_of(&trusted);
+		let second = child_of(&first);
+		let unlinked = header(4);
+		checker.check_header_chain(&trusted, &[first.clone(), second.clone()]).unwrap();
+
+		match *checker.check_header_chain(&trusted, &[first, second, unlinked.clone(), header(5)]).unwrap_err().kind() {
+			ErrorKind::InvalidHeaderAncestry(ref hash) => assert_eq!(*hash, format!("{}", unlinked.hash())),
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+	}
+
+	#[test]
+	fn header_sync_resumes_from_checkpoint() {
+		let checker = test_checker();
+		let trusted = header(1);
+		let first = child_of(&trusted);
+		let second = child_of(&first);
+		let third = child_of(&second);
+
+		let checkpoint = checker.check_header_chain_checkpoint(&trusted, &[first.clone(), second.clone()]).unwrap();
+		assert_eq!(checkpoint, SyncCheckpoint { last_number: 3, last_hash: second.hash() });
+		assert_eq!(checker.check_header_chain_checkpoint(&trusted, &[]).unwrap().last_hash, trusted.hash());
+
+		let resumed = checker.resume_header_chain(&checkpoint, &[third.clone()]).unwrap();
+		assert_eq!(resumed, SyncCheckpoint { last_number: 4, last_hash: third.hash() });
+		assert_eq!(checker.resume_header_chain(&checkpoint, &[]).unwrap(), checkpoint);
+	}
+
+	#[test]
+	fn header_sync_does_not_resume_off_checkpoint() {
+		let checker = test_checker();
+		let trusted = header(1);
+		let first = child_of(&trusted);
+		let checkpoint = SyncCheckpoint { last_number: 2, last_hash: header(2).hash() };
+
+		match *checker.resume_header_chain(&checkpoint, &[child_of(&first)]).unwrap_err().kind() {
+			ErrorKind::CheckpointMismatch(ref last_hash, _) => assert_eq!(*last_hash, format!("{}", checkpoint.last_hash)),
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+	}
+
+	#[test]
+	fn sync_checkpoint_roundtrips() {
+		let checkpoint = SyncCheckpoint { last_number: 42u64, last_hash: header(42).hash() };
+
+		let encoded = checkpoint.encode();
+		assert_eq!(encoded.len(), 8 + 32);
+		assert_eq!(SyncCheckpoint::decode(&mut &encoded[..]), Some(checkpoint.clone()));
+
+		let json = ::serde_json::to_string(&checkpoint).unwrap();
+		assert_eq!(::serde_json::from_str::<SyncCheckpoint<u64, Hash>>(&json).unwrap(), checkpoint);
+	}
+
+	#[test]
+	fn retry_policy_backs_off() {
+		let policy = RetryPolicy { count: 4, initial_delay_ms: 100, backoff_factor: 3 };
+		let delays: Vec<_> = (0..5).map(|retry| policy.delay(retry)).collect();
+		assert_eq!(delays, vec![
+			Some(Duration::from_millis(100)),
+			Some(Duration::from_millis(300)),
+			Some(Duration::from_millis(900)),
+			Some(Duration::from_millis(2700)),
+			None,
+		]);
+	}
+
+	#[test]
+	fn read_proof_size_is_limited() {
+		let remote_client = test_client::new();
+		let remote_block_header = remote_genesis_header(&remote_client);
+		let remote_block_hash = remote_block_header.hash();
+		let remote_read_proof = remote_client.read_proof(&BlockId::Number(0), b":auth:len").unwrap();
+		let proof_size: usize = remote_read_proof.iter().map(|node| node.len()).sum();
+
+		let local_checker = checker_with_header(remote_block_header);
+		let read_limited = |max_proof_size: usize| local_checker.check_read_proof(&RemoteReadRequest {
+			block: remote_block_hash,
+			key: b":auth:len".to_vec(),
+			max_proof_size: Some(max_proof_size),
+			retry: None,
+		}, remote_read_proof.clone());
+		assert!(read_limited(proof_size).unwrap().is_some());
+		match read_limited(proof_size - 1) {
+			Err(Error(ErrorKind::ProofTooLarge(size, max), _)) => assert_eq!((size, max), (proof_size, proof_size - 1)),
+			_ => panic!("expected ProofTooLarge"),
+		}
+	}
+
+	#[test]
+	fn header_by_hash_is_checked_against_requested_hash() {
+		let checker = test_checker();
+		let requested = header(1);
+		let request = RemoteHeaderByHashRequest { hash: requested.hash(), retry: None };
+		assert_eq!(checker.check_header_by_hash_proof(&request, Some(requested.clone())).unwrap(), requested);
+
+		match *checker.check_header_by_hash_proof(&request, Some(header(2))).unwrap_err().kind() {
+			ErrorKind::InvalidRemoteHeader(ref hash) => assert_eq!(*hash, format!("{}", requested.hash())),
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+		match *checker.check_header_by_hash_proof(&request, None).unwrap_err().kind() {
+			ErrorKind::UnknownBlock(_) => (),
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+	}
+
+	#[test]
+	fn cht_segment_is_checked_against_known_root() {
+		let (checker, hashes) = checker_with_cht_segment();
+		let request = RemoteChtRequest { segment: 0, retry: None };
+		assert_eq!(checker.check_cht_proof(&request, hashes.clone()).unwrap(), hashes);
+	}
+
+	#[test]
+	fn tampered_cht_segment_is_rejected() {
+		let (checker, hashes) = checker_with_cht_segment();
+		let request = RemoteChtRequest { segment: 0, retry: None };
+
+		let mut tampered = hashes.clone();
+		tampered[100] = header(100).hash();
+		match *checker.check_cht_proof(&request, tampered).unwrap_err().kind() {
+			ErrorKind::InvalidChtProof(segment) => assert_eq!(segment, 0),
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+		assert!(checker.check_cht_proof(&request, hashes[1..].to_vec()).is_err());
+	}
+
+	#[test]
+	fn cht_segment_beyond_tip_is_rejected() {
+		let (checker, hashes) = checker_with_cht_segment();
+		checker.note_cht_root(1, cht::build_cht_root::<<Header as HeaderT>::Hashing>(&hashes));
+
+		assert!(checker.check_cht_request(&RemoteChtRequest { segment: 0, retry: None }).is_ok());
+		for segment in vec![1, u64::max_value()] {
+			let request = RemoteChtRequest { segment, retry: None };
+			assert!(checker.check_cht_request(&request).is_err());
+			match *checker.check_cht_proof(&request, hashes.clone()).unwrap_err().kind() {
+				ErrorKind::UnknownChtSegment(s) => assert_eq!(s, segment),
+				ref kind => panic!("unexpected error: {:?}", kind),
+			}
+		}
+	}
+
+	#[test]
+	fn cht_segment_with_unknown_root_is_rejected() {
+		let (checker, hashes) = checker_with_cht_headers();
+
+		let request = RemoteChtRequest { segment: 0, retry: None };
+		match *checker.check_cht_request(&request).unwrap_err().kind() {
+			ErrorKind::UnknownChtSegment(segment) => assert_eq!(segment, 0),
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+		assert!(checker.check_cht_proof(&request, hashes).is_err());
+	}
 }
@@ -26,7 +26,7 @@ use wasmi::memory_units::{Pages, Bytes};
 use state_machine::{Externalities, CodeExecutor};
 use error::{Error, ErrorKind, Result};
 use wasm_utils::{DummyUserError};
-use primitives::{blake2_256, twox_128, twox_256};
+use primitives::{blake2_256, keccak_256, twox_128, twox_256};
 use primitives::hexdisplay::HexDisplay;
 use primitives::sandbox as sandbox_primitives;
 use triehash::ordered_trie_root;
@@ -326,6 +326,15 @@ impl_function_executor!(this: FunctionExecutor<'e, E>,
 		this.memory.set(out, &result).map_err(|_| DummyUserError)?;
 		Ok(())
 	},
+	ext_keccak_256(data: *const u8, len: u32, out: *mut u8) => {
+		let result = if len == 0 {
+			keccak_256(&[0u8; 0])
+		} else {
+			keccak_256(&this.memory.get(data, len as usize).map_err(|_| DummyUserError)?)
+		};
+		this.memory.set(out, &result).map_err(|_| DummyUserError)?;
+		Ok(())
+	},
 	ext_ed25519_verify(msg_data: *const u8, msg_len: u32, sig_data: *const u8, pubkey_data: *const u8) -> u32 => {
 		let mut sig = [0u8; 64];
 		this.memory.get_into(sig_data, &mut sig[..]).map_err(|_| DummyUserError)?;
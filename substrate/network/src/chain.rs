@@ -47,6 +47,15 @@ pub trait Client<Block: BlockT>: Send + Sync {
 
 	/// Get method execution proof.
 	fn execution_proof(&self, block: &Block::Hash, method: &str, data: &[u8]) -> Result<(Vec<u8>, Vec<Vec<u8>>), Error>;
+
+	/// Get storage read proof.
+	fn read_proof(&self, block: &Block::Hash, key: &[u8]) -> Result<Vec<Vec<u8>>, Error>;
+
+	/// Get storage read proof, covering all given keys.
+	fn read_batch_proof(&self, block: &Block::Hash, keys: &[Vec<u8>]) -> Result<Vec<Vec<u8>>, Error>;
+
+	/// Get hashes of the blocks of a CHT segment.
+	fn cht_hashes(&self, segment: u64) -> Result<Vec<Block::Hash>, Error>;
 }
 
 impl<B, E, Block> Client<Block> for PolkadotClient<B, E, Block> where
@@ -89,4 +98,16 @@ impl<B, E, Block> Client<Block> for PolkadotClient<B, E, Block> where
 	fn execution_proof(&self, block: &Block::Hash, method: &str, data: &[u8]) -> Result<(Vec<u8>, Vec<Vec<u8>>), Error> {
 		(self as &PolkadotClient<B, E, Block>).execution_proof(&BlockId::Hash(block.clone()), method, data)
 	}
+
+	fn read_proof(&self, block: &Block::Hash, key: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+		(self as &PolkadotClient<B, E, Block>).read_proof(&BlockId::Hash(block.clone()), key)
+	}
+
+	fn read_batch_proof(&self, block: &Block::Hash, keys: &[Vec<u8>]) -> Result<Vec<Vec<u8>>, Error> {
+		(self as &PolkadotClient<B, E, Block>).read_batch_proof(&BlockId::Hash(block.clone()), keys)
+	}
+
+	fn cht_hashes(&self, segment: u64) -> Result<Vec<Block::Hash>, Error> {
+		(self as &PolkadotClient<B, E, Block>).cht_hashes(segment)
+	}
 }
@@ -19,7 +19,8 @@
 use runtime_primitives::traits::{Block as BlockT, Header as HeaderT};
 use service::Role as RoleFlags;
 
-pub use self::generic::{BlockAnnounce, RemoteCallRequest, ConsensusVote, SignedConsensusVote, FromBlock, Body};
+pub use self::generic::{BlockAnnounce, RemoteCallRequest, RemoteReadRequest, RemoteBatchReadRequest,
+	RemoteHeaderRequest, ConsensusVote, SignedConsensusVote, FromBlock, Body};
 
 pub type RequestId = u64;
 
@@ -44,6 +45,12 @@ pub type BlockRequest<B> = generic::BlockRequest<
 	<<B as BlockT>::Header as HeaderT>::Number,
 >;
 
+/// Type alias for using the remote header response type using block type parameters.
+pub type RemoteHeaderResponse<B> = generic::RemoteHeaderResponse<<B as BlockT>::Header>;
+
+/// Type alias for using the remote CHT segment response type using block type parameters.
+pub type RemoteChtResponse<B> = generic::RemoteChtResponse<<B as BlockT>::Hash>;
+
 /// Type alias for using the localized bft message type using block type parameters.
 pub type LocalizedBftMessage<B> = generic::LocalizedBftMessage<
 	B,
@@ -166,6 +173,24 @@ pub struct RemoteCallResponse {
 	pub proof: Vec<Vec<u8>>,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+/// Remote read response.
+pub struct RemoteReadResponse {
+	/// Id of a request this response was made for.
+	pub id: RequestId,
+	/// Read proof.
+	pub proof: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+/// Remote CHT segment request.
+pub struct RemoteChtRequest {
+	/// Unique request id.
+	pub id: RequestId,
+	/// Requested CHT segment.
+	pub segment: u64,
+}
+
 /// Generic types.
 pub mod generic {
 	use primitives::AuthorityId;
@@ -173,7 +198,8 @@ pub mod generic {
 	use runtime_primitives::bft::Justification;
 	use ed25519;
 
-	use super::{Role, BlockAttribute, RemoteCallResponse, RequestId, Transactions, Direction};
+	use super::{Role, BlockAttribute, RemoteCallResponse, RemoteReadResponse, RemoteChtRequest, RequestId, Transactions,
+		Direction};
 
 	use primitives::bytes;
 
@@ -319,6 +345,20 @@ pub mod generic {
 		RemoteCallRequest(RemoteCallRequest<Hash>),
 		/// Remote method call response.
 		RemoteCallResponse(RemoteCallResponse),
+		/// Remote storage read request.
+		RemoteReadRequest(RemoteReadRequest<Hash>),
+		/// Remote storage read response.
+		RemoteReadResponse(RemoteReadResponse),
+		/// Remote storage batch read request. Answered with `RemoteReadResponse`.
+		RemoteBatchReadRequest(RemoteBatchReadRequest<Hash>),
+		/// Remote header request, by hash.
+		RemoteHeaderRequest(RemoteHeaderRequest<Hash>),
+		/// Remote header response.
+		RemoteHeaderResponse(RemoteHeaderResponse<Header>),
+		/// Remote CHT segment request.
+		RemoteChtRequest(RemoteChtRequest),
+		/// Remote CHT segment response.
+		RemoteChtResponse(RemoteChtResponse<Hash>),
 	}
 
 	/// Status sent on connection.
@@ -387,4 +427,53 @@ pub mod generic {
 		/// Call data.
 		pub data: Vec<u8>,
 	}
+
+	#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+	/// Remote storage read request.
+	pub struct RemoteReadRequest<H> {
+		/// Unique request id.
+		pub id: RequestId,
+		/// Block at which to perform call.
+		pub block: H,
+		/// Storage key.
+		pub key: Vec<u8>,
+	}
+
+	#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+	/// Remote storage batch read request.
+	pub struct RemoteBatchReadRequest<H> {
+		/// Unique request id.
+		pub id: RequestId,
+		/// Block at which to perform call.
+		pub block: H,
+		/// Storage keys.
+		pub keys: Vec<Vec<u8>>,
+	}
+
+	#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+	/// Remote header request.
+	pub struct RemoteHeaderRequest<H> {
+		/// Unique request id.
+		pub id: RequestId,
+		/// Hash of the requested header.
+		pub hash: H,
+	}
+
+	#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+	/// Remote header response.
+	pub struct RemoteHeaderResponse<Header> {
+		/// Id of a request this response was made for.
+		pub id: RequestId,
+		/// Requested header, if known to the remote node.
+		pub header: Option<Header>,
+	}
+
+	#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+	/// Remote CHT segment response.
+	pub struct RemoteChtResponse<H> {
+		/// Id of a request this response was made for.
+		pub id: RequestId,
+		/// Hashes of the segment blocks, if the segment is known to the remote node.
+		pub hashes: Option<Vec<H>>,
+	}
 }
@@ -19,13 +19,14 @@
 use std::collections::VecDeque;
 use std::sync::{Arc, Weak};
 use std::time::{Instant, Duration};
-use futures::{Future, Poll};
+use futures::{Async, Future, Poll};
 use futures::sync::oneshot::{channel, Receiver, Sender};
 use linked_hash_map::LinkedHashMap;
 use linked_hash_map::Entry;
 use parking_lot::Mutex;
 use client;
-use client::light::{Fetcher, FetchChecker, RemoteCallRequest};
+use client::light::{Fetcher, FetchChecker, RemoteCallRequest, RemoteReadRequest, RemoteBatchReadRequest,
+	RemoteHeaderByHashRequest, RemoteChtRequest};
 use io::SyncIo;
 use message;
 use network::PeerId;
@@ -36,7 +37,7 @@ use runtime_primitives::traits::{Block as BlockT, Header as HeaderT};
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
 
 /// On-demand service API.
-pub trait OnDemandService: Send + Sync {
+pub trait OnDemandService<Block: BlockT>: Send + Sync {
 	/// When new node is connected.
 	fn on_connect(&self, peer: PeerId, role: service::Role);
 
@@ -46,8 +47,17 @@ pub trait OnDemandService: Send + Sync {
 	/// Maintain peers requests.
 	fn maintain_peers(&self, io: &mut SyncIo);
 
-	/// When response is received from remote node.
-	fn on_remote_response(&self, io: &mut SyncIo, peer: PeerId, response: message::RemoteCallResponse);
+	/// When call response is received from remote node.
+	fn on_remote_call_response(&self, io: &mut SyncIo, peer: PeerId, response: message::RemoteCallResponse);
+
+	/// When read response is received from remote node.
+	fn on_remote_read_response(&self, io: &mut SyncIo, peer: PeerId, response: message::RemoteReadResponse);
+
+	/// When header response is received from remote node.
+	fn on_remote_header_response(&self, io: &mut SyncIo, peer: PeerId, response: message::RemoteHeaderResponse<Block>);
+
+	/// When CHT segment response is received from remote node.
+	fn on_remote_cht_response(&self, io: &mut SyncIo, peer: PeerId, response: message::RemoteChtResponse<Block>);
 }
 
 /// On-demand requests service. Dispatches requests to appropriate peers.
@@ -57,33 +67,50 @@ pub struct OnDemand<B: BlockT, E: service::ExecuteInContext<B>> {
 }
 
 /// On-demand response.
-pub struct Response {
-	receiver: Receiver<client::CallResult>,
+pub struct Response<T> {
+	receiver: Receiver<client::error::Result<T>>,
 }
 
 #[derive(Default)]
 struct OnDemandCore<B: BlockT, E: service::ExecuteInContext<B>> {
 	service: Weak<E>,
 	next_request_id: u64,
-	pending_requests: VecDeque<Request<B::Hash>>,
-	active_peers: LinkedHashMap<PeerId, Request<B::Hash>>,
+	pending_requests: VecDeque<Request<B>>,
+	active_peers: LinkedHashMap<PeerId, Request<B>>,
 	idle_peers: VecDeque<PeerId>,
 }
 
-struct Request<H> {
+struct Request<Block: BlockT> {
 	id: u64,
 	timestamp: Instant,
-	sender: Sender<client::CallResult>,
-	request: RemoteCallRequest<H>,
+	data: RequestData<Block>,
+}
+
+enum RequestData<Block: BlockT> {
+	RemoteCall(RemoteCallRequest<Block::Hash>, Sender<client::error::Result<client::CallResult>>),
+	RemoteRead(RemoteReadRequest<Block::Hash>, Sender<client::error::Result<Option<Vec<u8>>>>),
+	RemoteBatchRead(RemoteBatchReadRequest<Block::Hash>, Sender<client::error::Result<Vec<Option<Vec<u8>>>>>),
+	RemoteHeader(RemoteHeaderByHashRequest<Block::Hash>, Sender<client::error::Result<Block::Header>>),
+	RemoteCht(RemoteChtRequest, Sender<client::error::Result<Vec<Block::Hash>>>),
+}
+
+enum Accept<Block: BlockT> {
+	Ok,
+	CheckFailed(client::error::Error, RequestData<Block>),
+	Unexpected(RequestData<Block>),
 }
 
-impl Future for Response {
-	type Item = client::CallResult;
+impl<T> Future for Response<T> {
+	type Item = T;
 	type Error = client::error::Error;
 
 	fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
-		self.receiver.poll()
-			.map_err(|_| client::error::ErrorKind::RemoteFetchCancelled.into())
+		match self.receiver.poll() {
+			Ok(Async::Ready(Ok(value))) => Ok(Async::Ready(value)),
+			Ok(Async::Ready(Err(error))) => Err(error),
+			Ok(Async::NotReady) => Ok(Async::NotReady),
+			Err(_) => Err(client::error::ErrorKind::RemoteFetchCancelled.into()),
+		}
 	}
 }
 
@@ -111,23 +138,88 @@ impl<B: BlockT, E> OnDemand<B, E> where
 	}
 
 	/// Execute method call on remote node, returning execution result and proof.
-	pub fn remote_call(&self, request: RemoteCallRequest<B::Hash>) -> Response {
+	pub fn remote_call(&self, request: RemoteCallRequest<B::Hash>) -> Response<client::CallResult> {
+		let (sender, receiver) = channel();
+		self.schedule_request(RequestData::RemoteCall(request, sender));
+		Response { receiver }
+	}
+
+	/// Read storage value on remote node, returning the value checked against the read proof.
+	pub fn remote_read(&self, request: RemoteReadRequest<B::Hash>) -> Response<Option<Vec<u8>>> {
+		let (sender, receiver) = channel();
+		self.schedule_request(RequestData::RemoteRead(request, sender));
+		Response { receiver }
+	}
+
+	/// Read storage values on remote node, returning the values checked against the single read proof.
+	pub fn remote_read_batch(&self, request: RemoteBatchReadRequest<B::Hash>) -> Response<Vec<Option<Vec<u8>>>> {
+		let (sender, receiver) = channel();
+		self.schedule_request(RequestData::RemoteBatchRead(request, sender));
+		Response { receiver }
+	}
+
+	/// Fetch header with given hash from remote node, returning the header checked against the hash.
+	pub fn remote_header_by_hash(&self, request: RemoteHeaderByHashRequest<B::Hash>) -> Response<B::Header> {
+		let (sender, receiver) = channel();
+		self.schedule_request(RequestData::RemoteHeader(request, sender));
+		Response { receiver }
+	}
+
+	/// Fetch block hashes of the CHT segment from remote node, returning the hashes checked against the segment root.
+	/// Requests which can't be checked locally, because the segment is beyond the local chain or its
+	/// root is unknown, fail without being sent to any peer.
+	pub fn remote_cht(&self, request: RemoteChtRequest) -> Response<Vec<B::Hash>> {
 		let (sender, receiver) = channel();
-		let result = Response {
-			receiver: receiver,
+		match self.checker.check_cht_request(&request) {
+			Ok(()) => self.schedule_request(RequestData::RemoteCht(request, sender)),
+			Err(error) => { let _ = sender.send(Err(error)); },
+		}
+		Response { receiver }
+	}
+
+	/// Schedule && dispatch request.
+	fn schedule_request(&self, data: RequestData<B>) {
+		let mut core = self.core.lock();
+		core.insert(data);
+		core.dispatch();
+	}
+
+	/// Try to accept response from given peer.
+	fn accept_response<F: FnOnce(RequestData<B>) -> Accept<B>>(&self, rtype: &str, io: &mut SyncIo, peer: PeerId, request_id: u64, try_accept: F) {
+		let mut core = self.core.lock();
+		let request = match core.remove(peer, request_id) {
+			Some(request) => request,
+			None => {
+				trace!(target: "sync", "Invalid remote {} response from peer {}", rtype, peer);
+				io.disconnect_peer(peer);
+				core.remove_peer(peer);
+				return;
+			},
+		};
+
+		let retry_request_data = match try_accept(request.data) {
+			Accept::Ok => None,
+			Accept::CheckFailed(error, retry_request_data) => {
+				trace!(target: "sync", "Failed to check remote {} response from peer {}: {}", rtype, peer, error);
+				Some(retry_request_data)
+			},
+			Accept::Unexpected(retry_request_data) => {
+				trace!(target: "sync", "Unexpected response to remote {} from peer {}", rtype, peer);
+				Some(retry_request_data)
+			},
 		};
 
-		{
-			let mut core = self.core.lock();
-			core.insert(sender, request);
-			core.dispatch();
+		if let Some(request_data) = retry_request_data {
+			io.disconnect_peer(peer);
+			core.remove_peer(peer);
+			core.insert(request_data);
 		}
 
-		result
+		core.dispatch();
 	}
 }
 
-impl<B, E> OnDemandService for OnDemand<B, E> where
+impl<B, E> OnDemandService<B> for OnDemand<B, E> where
 	B: BlockT,
 	E: service::ExecuteInContext<B>,
 	B::Header: HeaderT<Number=u64>,
@@ -157,29 +249,80 @@ impl<B, E> OnDemandService for OnDemand<B, E> where
 		core.dispatch();
 	}
 
-	fn on_remote_response(&self, io: &mut SyncIo, peer: PeerId, response: message::RemoteCallResponse) {
-		let mut core = self.core.lock();
-		match core.remove(peer, response.id) {
-			Some(request) => match self.checker.check_execution_proof(&request.request, response.proof) {
+	fn on_remote_call_response(&self, io: &mut SyncIo, peer: PeerId, response: message::RemoteCallResponse) {
+		self.accept_response("call", io, peer, response.id, |request| match request {
+			RequestData::RemoteCall(request, sender) => match self.checker.check_execution_proof(&request, response.proof) {
 				Ok(response) => {
 					// we do not bother if receiver has been dropped already
-					let _ = request.sender.send(response);
+					let _ = sender.send(Ok(response));
+					Accept::Ok
 				},
-				Err(error) => {
-					trace!(target: "sync", "Failed to check remote response from peer {}: {}", peer, error);
-					io.disconnect_peer(peer);
-					core.remove_peer(peer);
-					core.insert(request.sender, request.request);
+				Err(error) => Accept::CheckFailed(error, RequestData::RemoteCall(request, sender)),
+			},
+			data => Accept::Unexpected(data),
+		})
+	}
+
+	fn on_remote_read_response(&self, io: &mut SyncIo, peer: PeerId, response: message::RemoteReadResponse) {
+		self.accept_response("read", io, peer, response.id, |request| match request {
+			RequestData::RemoteRead(request, sender) => match self.checker.check_read_proof(&request, response.proof) {
+				Ok(response) => {
+					// we do not bother if receiver has been dropped already
+					let _ = sender.send(Ok(response));
+					Accept::Ok
 				},
+				Err(error) => Accept::CheckFailed(error, RequestData::RemoteRead(request, sender)),
 			},
-			None => {
-				trace!(target: "sync", "Invalid remote response from peer {}", peer);
-				io.disconnect_peer(peer);
-				core.remove_peer(peer);
+			RequestData::RemoteBatchRead(request, sender) => match self.checker.check_read_batch_proof(&request, response.proof) {
+				Ok(response) => {
+					// we do not bother if receiver has been dropped already
+					let _ = sender.send(Ok(response));
+					Accept::Ok
+				},
+				Err(error) => Accept::CheckFailed(error, RequestData::RemoteBatchRead(request, sender)),
 			},
-		}
+			data => Accept::Unexpected(data),
+		})
+	}
 
-		core.dispatch();
+	fn on_remote_header_response(&self, io: &mut SyncIo, peer: PeerId, response: message::RemoteHeaderResponse<B>) {
+		self.accept_response("header", io, peer, response.id, |request| match request {
+			RequestData::RemoteHeader(request, sender) => match self.checker.check_header_by_hash_proof(&request, response.header) {
+				Ok(response) => {
+					// we do not bother if receiver has been dropped already
+					let _ = sender.send(Ok(response));
+					Accept::Ok
+				},
+				Err(error) => Accept::CheckFailed(error, RequestData::RemoteHeader(request, sender)),
+			},
+			data => Accept::Unexpected(data),
+		})
+	}
+
+	fn on_remote_cht_response(&self, io: &mut SyncIo, peer: PeerId, response: message::RemoteChtResponse<B>) {
+		self.accept_response("cht", io, peer, response.id, |request| match request {
+			RequestData::RemoteCht(request, sender) => match response.hashes {
+				// the peer does not know the segment, which is no reason to disconnect it
+				None => {
+					let _ = sender.send(Err(client::error::ErrorKind::UnknownChtSegment(request.segment).into()));
+					Accept::Ok
+				},
+				Some(hashes) => match self.checker.check_cht_proof(&request, hashes) {
+					Ok(response) => {
+						// we do not bother if receiver has been dropped already
+						let _ = sender.send(Ok(response));
+						Accept::Ok
+					},
+					// the segment is no longer checkable locally, which is not the peer's fault
+					Err(client::error::Error(client::error::ErrorKind::UnknownChtSegment(segment), _)) => {
+						let _ = sender.send(Err(client::error::ErrorKind::UnknownChtSegment(segment).into()));
+						Accept::Ok
+					},
+					Err(error) => Accept::CheckFailed(error, RequestData::RemoteCht(request, sender)),
+				},
+			},
+			data => Accept::Unexpected(data),
+		})
 	}
 }
 
@@ -188,11 +331,31 @@ impl<B, E> Fetcher<B> for OnDemand<B, E> where
 	E: service::ExecuteInContext<B>,
 	B::Header: HeaderT<Number=u64>,
 {
-	type RemoteCallResult = Response;
+	type RemoteCallResult = Response<client::CallResult>;
+	type RemoteReadResult = Response<Option<Vec<u8>>>;
+	type RemoteBatchReadResult = Response<Vec<Option<Vec<u8>>>>;
+	type RemoteHeaderResult = Response<B::Header>;
+	type RemoteChtResult = Response<Vec<B::Hash>>;
 
 	fn remote_call(&self, request: RemoteCallRequest<B::Hash>) -> Self::RemoteCallResult {
 		OnDemand::remote_call(self, request)
 	}
+
+	fn remote_read(&self, request: RemoteReadRequest<B::Hash>) -> Self::RemoteReadResult {
+		OnDemand::remote_read(self, request)
+	}
+
+	fn remote_read_batch(&self, request: RemoteBatchReadRequest<B::Hash>) -> Self::RemoteBatchReadResult {
+		OnDemand::remote_read_batch(self, request)
+	}
+
+	fn remote_header_by_hash(&self, request: RemoteHeaderByHashRequest<B::Hash>) -> Self::RemoteHeaderResult {
+		OnDemand::remote_header_by_hash(self, request)
+	}
+
+	fn remote_cht(&self, request: RemoteChtRequest) -> Self::RemoteChtResult {
+		OnDemand::remote_cht(self, request)
+	}
 }
 
 impl<B, E> OnDemandCore<B, E> where
@@ -230,19 +393,18 @@ impl<B, E> OnDemandCore<B, E> where
 		}
 	}
 
-	pub fn insert(&mut self, sender: Sender<client::CallResult>, request: RemoteCallRequest<B::Hash>) {
+	pub fn insert(&mut self, data: RequestData<B>) {
 		let request_id = self.next_request_id;
 		self.next_request_id += 1;
 
 		self.pending_requests.push_back(Request {
 			id: request_id,
 			timestamp: Instant::now(),
-			sender,
-			request,
+			data,
 		});
 	}
 
-	pub fn remove(&mut self, peer: PeerId, id: u64) -> Option<Request<B::Hash>> {
+	pub fn remove(&mut self, peer: PeerId, id: u64) -> Option<Request<B>> {
 		match self.active_peers.entry(peer) {
 			Entry::Occupied(entry) => match entry.get().id == id {
 				true => {
@@ -271,21 +433,43 @@ impl<B, E> OnDemandCore<B, E> where
 			request.timestamp = Instant::now();
 			trace!(target: "sync", "Dispatching remote request {} to peer {}", request.id, peer);
 
-			service.execute_in_context(|ctx, protocol| {
-				let message = message::RemoteCallRequest {
-					id: request.id,
-					block: request.request.block,
-					method: request.request.method.clone(),
-					data: request.request.call_data.clone(),
-				};
-
-				protocol.send_message(ctx, peer, message::generic::Message::RemoteCallRequest(message))
-			});
+			service.execute_in_context(|ctx, protocol| protocol.send_message(ctx, peer, request.message()));
 			self.active_peers.insert(peer, request);
 		}
 	}
 }
 
+impl<Block: BlockT> Request<Block> {
+	fn message(&self) -> message::Message<Block> {
+		match self.data {
+			RequestData::RemoteCall(ref data, _) => message::generic::Message::RemoteCallRequest(message::RemoteCallRequest {
+				id: self.id,
+				block: data.block,
+				method: data.method.clone(),
+				data: data.call_data.clone(),
+			}),
+			RequestData::RemoteRead(ref data, _) => message::generic::Message::RemoteReadRequest(message::RemoteReadRequest {
+				id: self.id,
+				block: data.block,
+				key: data.key.clone(),
+			}),
+			RequestData::RemoteBatchRead(ref data, _) => message::generic::Message::RemoteBatchReadRequest(message::RemoteBatchReadRequest {
+				id: self.id,
+				block: data.block,
+				keys: data.keys.clone(),
+			}),
+			RequestData::RemoteHeader(ref data, _) => message::generic::Message::RemoteHeaderRequest(message::RemoteHeaderRequest {
+				id: self.id,
+				hash: data.hash,
+			}),
+			RequestData::RemoteCht(ref data, _) => message::generic::Message::RemoteChtRequest(message::RemoteChtRequest {
+				id: self.id,
+				segment: data.segment,
+			}),
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use std::collections::VecDeque;
@@ -294,7 +478,8 @@ mod tests {
 	use futures::Future;
 	use parking_lot::RwLock;
 	use client;
-	use client::light::{FetchChecker, RemoteCallRequest};
+	use client::light::{FetchChecker, RemoteCallRequest, RemoteReadRequest, RemoteBatchReadRequest,
+		RemoteHeaderByHashRequest, RemoteChtRequest};
 	use io::NetSyncIo;
 	use message;
 	use network::PeerId;
@@ -302,7 +487,8 @@ mod tests {
 	use service::{Role, ExecuteInContext};
 	use test::TestIo;
 	use super::{REQUEST_TIMEOUT, OnDemand, OnDemandService};
-	use test_client::runtime::{Block, Hash};
+	use runtime_primitives::traits::Header as HeaderT;
+	use test_client::runtime::{Block, Hash, Header};
 
 	struct DummyExecutor;
 	struct DummyFetchChecker { ok: bool }
@@ -321,6 +507,34 @@ mod tests {
 				false => Err(client::error::ErrorKind::Backend("Test error".into()).into()),
 			}
 		}
+
+		fn check_read_proof(&self, _request: &RemoteReadRequest<Hash>, _remote_proof: Vec<Vec<u8>>) -> client::error::Result<Option<Vec<u8>>> {
+			match self.ok {
+				true => Ok(Some(vec![42])),
+				false => Err(client::error::ErrorKind::Backend("Test error".into()).into()),
+			}
+		}
+
+		fn check_read_batch_proof(&self, request: &RemoteBatchReadRequest<Hash>, _remote_proof: Vec<Vec<u8>>) -> client::error::Result<Vec<Option<Vec<u8>>>> {
+			match self.ok {
+				true => Ok(request.keys.iter().map(|key| Some(key.clone())).collect()),
+				false => Err(client::error::ErrorKind::Backend("Test error".into()).into()),
+			}
+		}
+
+		fn check_cht_request(&self, request: &RemoteChtRequest) -> client::error::Result<()> {
+			match request.segment {
+				0 => Ok(()),
+				segment => Err(client::error::ErrorKind::UnknownChtSegment(segment).into()),
+			}
+		}
+
+		fn check_cht_proof(&self, _request: &RemoteChtRequest, remote_hashes: Vec<Hash>) -> client::error::Result<Vec<Hash>> {
+			match self.ok {
+				true => Ok(remote_hashes),
+				false => Err(client::error::ErrorKind::Backend("Test error".into()).into()),
+			}
+		}
 	}
 
 	fn dummy(ok: bool) -> (Arc<DummyExecutor>, Arc<OnDemand<Block, DummyExecutor>>) {
@@ -336,7 +550,7 @@ mod tests {
 	}
 
 	fn receive_response(on_demand: &OnDemand<Block, DummyExecutor>, network: &mut TestIo, peer: PeerId, id: message::RequestId) {
-		on_demand.on_remote_response(network, peer, message::RemoteCallResponse {
+		on_demand.on_remote_call_response(network, peer, message::RemoteCallResponse {
 			id: id,
 			proof: vec![vec![2]],
 		});
@@ -372,7 +586,7 @@ mod tests {
 		assert_eq!(vec![0, 1], on_demand.core.lock().idle_peers.iter().cloned().collect::<Vec<_>>());
 		assert!(on_demand.core.lock().active_peers.is_empty());
 
-		on_demand.remote_call(RemoteCallRequest { block: Default::default(), method: "test".into(), call_data: vec![] });
+		on_demand.remote_call(RemoteCallRequest { block: Default::default(), method: "test".into(), call_data: vec![], expected_result: None, retry: None });
 		assert_eq!(vec![1], on_demand.core.lock().idle_peers.iter().cloned().collect::<Vec<_>>());
 		assert_eq!(vec![0], on_demand.core.lock().active_peers.keys().cloned().collect::<Vec<_>>());
 
@@ -390,7 +604,7 @@ mod tests {
 		let mut network = TestIo::new(&queue, None);
 		on_demand.on_connect(0, Role::FULL);
 
-		on_demand.remote_call(RemoteCallRequest { block: Default::default(), method: "test".into(), call_data: vec![] });
+		on_demand.remote_call(RemoteCallRequest { block: Default::default(), method: "test".into(), call_data: vec![], expected_result: None, retry: None });
 		receive_response(&*on_demand, &mut network, 0, 1);
 		assert!(network.to_disconnect.contains(&0));
 		assert_eq!(on_demand.core.lock().pending_requests.len(), 1);
@@ -403,7 +617,7 @@ mod tests {
 		let mut network = TestIo::new(&queue, None);
 		on_demand.on_connect(0, Role::FULL);
 
-		on_demand.remote_call(RemoteCallRequest { block: Default::default(), method: "test".into(), call_data: vec![] });
+		on_demand.remote_call(RemoteCallRequest { block: Default::default(), method: "test".into(), call_data: vec![], expected_result: None, retry: None });
 		receive_response(&*on_demand, &mut network, 0, 0);
 		assert!(network.to_disconnect.contains(&0));
 		assert_eq!(on_demand.core.lock().pending_requests.len(), 1);
@@ -427,7 +641,7 @@ mod tests {
 		let mut network = TestIo::new(&queue, None);
 		on_demand.on_connect(0, Role::FULL);
 
-		let response = on_demand.remote_call(RemoteCallRequest { block: Default::default(), method: "test".into(), call_data: vec![] });
+		let response = on_demand.remote_call(RemoteCallRequest { block: Default::default(), method: "test".into(), call_data: vec![], expected_result: None, retry: None });
 		let thread = ::std::thread::spawn(move || {
 			let result = response.wait().unwrap();
 			assert_eq!(result.return_data, vec![42]);
@@ -436,4 +650,153 @@ mod tests {
 		receive_response(&*on_demand, &mut network, 0, 0);
 		thread.join().unwrap();
 	}
+
+	#[test]
+	fn disconnects_from_peer_on_wrong_response_type() {
+		let (_x, on_demand) = dummy(false);
+		let queue = RwLock::new(VecDeque::new());
+		let mut network = TestIo::new(&queue, None);
+		on_demand.on_connect(0, Role::FULL);
+
+		on_demand.remote_call(RemoteCallRequest { block: Default::default(), method: "test".into(), call_data: vec![], expected_result: None, retry: None });
+		on_demand.on_remote_read_response(&mut network, 0, message::RemoteReadResponse {
+			id: 0,
+			proof: vec![vec![2]],
+		});
+		assert!(network.to_disconnect.contains(&0));
+		assert_eq!(on_demand.core.lock().pending_requests.len(), 1);
+	}
+
+	#[test]
+	fn receives_remote_read_response() {
+		let (_x, on_demand) = dummy(true);
+		let queue = RwLock::new(VecDeque::new());
+		let mut network = TestIo::new(&queue, None);
+		on_demand.on_connect(0, Role::FULL);
+
+		let response = on_demand.remote_read(RemoteReadRequest { block: Default::default(), key: b":key".to_vec(), max_proof_size: None, retry: None });
+		let thread = ::std::thread::spawn(move || {
+			let result = response.wait().unwrap();
+			assert_eq!(result, Some(vec![42]));
+		});
+
+		on_demand.on_remote_read_response(&mut network, 0, message::RemoteReadResponse {
+			id: 0,
+			proof: vec![vec![2]],
+		});
+		thread.join().unwrap();
+	}
+
+	#[test]
+	fn receives_remote_batch_read_response() {
+		let (_x, on_demand) = dummy(true);
+		let queue = RwLock::new(VecDeque::new());
+		let mut network = TestIo::new(&queue, None);
+		on_demand.on_connect(0, Role::FULL);
+
+		let keys = vec![b":key1".to_vec(), b":key2".to_vec()];
+		let response = on_demand.remote_read_batch(RemoteBatchReadRequest { block: Default::default(), keys: keys.clone(), retry: None });
+		let thread = ::std::thread::spawn(move || {
+			let result = response.wait().unwrap();
+			assert_eq!(result, keys.into_iter().map(Some).collect::<Vec<_>>());
+		});
+
+		on_demand.on_remote_read_response(&mut network, 0, message::RemoteReadResponse {
+			id: 0,
+			proof: vec![vec![2]],
+		});
+		thread.join().unwrap();
+	}
+
+	#[test]
+	fn receives_remote_header_response() {
+		let (_x, on_demand) = dummy(true);
+		let queue = RwLock::new(VecDeque::new());
+		let mut network = TestIo::new(&queue, None);
+		on_demand.on_connect(0, Role::FULL);
+
+		let header: Header = HeaderT::new(1, Default::default(), Default::default(), Default::default(), Default::default());
+		let response = on_demand.remote_header_by_hash(RemoteHeaderByHashRequest { hash: header.hash(), retry: None });
+		let expected = header.clone();
+		let thread = ::std::thread::spawn(move || {
+			let result = response.wait().unwrap();
+			assert_eq!(result, expected);
+		});
+
+		on_demand.on_remote_header_response(&mut network, 0, message::generic::RemoteHeaderResponse {
+			id: 0,
+			header: Some(header),
+		});
+		thread.join().unwrap();
+	}
+
+	#[test]
+	fn disconnects_from_peer_on_header_hash_mismatch() {
+		let (_x, on_demand) = dummy(true);
+		let queue = RwLock::new(VecDeque::new());
+		let mut network = TestIo::new(&queue, None);
+		on_demand.on_connect(0, Role::FULL);
+
+		let requested: Header = HeaderT::new(1, Default::default(), Default::default(), Default::default(), Default::default());
+		let served: Header = HeaderT::new(2, Default::default(), Default::default(), Default::default(), Default::default());
+		on_demand.remote_header_by_hash(RemoteHeaderByHashRequest { hash: requested.hash(), retry: None });
+		on_demand.on_remote_header_response(&mut network, 0, message::generic::RemoteHeaderResponse {
+			id: 0,
+			header: Some(served),
+		});
+		assert!(network.to_disconnect.contains(&0));
+		assert_eq!(on_demand.core.lock().pending_requests.len(), 1);
+	}
+
+	#[test]
+	fn receives_remote_cht_response() {
+		let (_x, on_demand) = dummy(true);
+		let queue = RwLock::new(VecDeque::new());
+		let mut network = TestIo::new(&queue, None);
+		on_demand.on_connect(0, Role::FULL);
+
+		let hashes: Vec<Hash> = vec![[1u8; 32].into(), [2u8; 32].into()];
+		let response = on_demand.remote_cht(RemoteChtRequest { segment: 0, retry: None });
+		let expected = hashes.clone();
+		let thread = ::std::thread::spawn(move || {
+			let result = response.wait().unwrap();
+			assert_eq!(result, expected);
+		});
+
+		on_demand.on_remote_cht_response(&mut network, 0, message::generic::RemoteChtResponse {
+			id: 0,
+			hashes: Some(hashes),
+		});
+		thread.join().unwrap();
+	}
+
+	#[test]
+	fn uncheckable_cht_request_fails_without_dispatch() {
+		let (_x, on_demand) = dummy(true);
+		on_demand.on_connect(0, Role::FULL);
+
+		match *on_demand.remote_cht(RemoteChtRequest { segment: 1, retry: None }).wait().unwrap_err().kind() {
+			client::error::ErrorKind::UnknownChtSegment(segment) => assert_eq!(segment, 1),
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+		assert!(on_demand.core.lock().pending_requests.is_empty());
+		assert!(on_demand.core.lock().active_peers.is_empty());
+	}
+
+	#[test]
+	fn keeps_peer_not_knowing_cht_segment() {
+		let (_x, on_demand) = dummy(true);
+		let queue = RwLock::new(VecDeque::new());
+		let mut network = TestIo::new(&queue, None);
+		on_demand.on_connect(0, Role::FULL);
+
+		let response = on_demand.remote_cht(RemoteChtRequest { segment: 0, retry: None });
+		on_demand.on_remote_cht_response(&mut network, 0, message::generic::RemoteChtResponse {
+			id: 0,
+			hashes: None,
+		});
+		assert!(response.wait().is_err());
+		assert!(network.to_disconnect.is_empty());
+		assert_eq!(vec![0], on_demand.core.lock().idle_peers.iter().cloned().collect::<Vec<_>>());
+	}
 }
@@ -45,7 +45,7 @@ const MAX_BLOCK_DATA_RESPONSE: u32 = 128;
 pub struct Protocol<B: BlockT> {
 	config: ProtocolConfig,
 	chain: Arc<Client<B>>,
-	on_demand: Option<Arc<OnDemandService>>,
+	on_demand: Option<Arc<OnDemandService<B>>>,
 	genesis_hash: B::Hash,
 	sync: RwLock<ChainSync<B>>,
 	consensus: Mutex<Consensus<B>>,
@@ -108,7 +108,7 @@ impl<B: BlockT> Protocol<B> where
 	pub fn new(
 		config: ProtocolConfig,
 		chain: Arc<Client<B>>,
-		on_demand: Option<Arc<OnDemandService>>,
+		on_demand: Option<Arc<OnDemandService<B>>>,
 		transaction_pool: Arc<TransactionPool<B>>
 	) -> error::Result<Self>  {
 		let info = chain.info()?;
@@ -182,7 +182,14 @@ impl<B: BlockT> Protocol<B> where
 			GenericMessage::BftMessage(m) => self.on_bft_message(io, peer_id, m, HashingFor::<B>::hash(data)),
 			GenericMessage::Transactions(m) => self.on_transactions(io, peer_id, m),
 			GenericMessage::RemoteCallRequest(request) => self.on_remote_call_request(io, peer_id, request),
-			GenericMessage::RemoteCallResponse(response) => self.on_remote_call_response(io, peer_id, response)
+			GenericMessage::RemoteCallResponse(response) => self.on_remote_call_response(io, peer_id, response),
+			GenericMessage::RemoteReadRequest(request) => self.on_remote_read_request(io, peer_id, request),
+			GenericMessage::RemoteReadResponse(response) => self.on_remote_read_response(io, peer_id, response),
+			GenericMessage::RemoteBatchReadRequest(request) => self.on_remote_batch_read_request(io, peer_id, request),
+			GenericMessage::RemoteHeaderRequest(request) => self.on_remote_header_request(io, peer_id, request),
+			GenericMessage::RemoteHeaderResponse(response) => self.on_remote_header_response(io, peer_id, response),
+			GenericMessage::RemoteChtRequest(request) => self.on_remote_cht_request(io, peer_id, request),
+			GenericMessage::RemoteChtResponse(response) => self.on_remote_cht_response(io, peer_id, response),
 		}
 	}
 
@@ -529,7 +536,90 @@ impl<B: BlockT> Protocol<B> where
 
 	fn on_remote_call_response(&self, io: &mut SyncIo, peer_id: PeerId, response: message::RemoteCallResponse) {
 		trace!(target: "sync", "Remote response {} from {}", response.id, peer_id);
-		self.on_demand.as_ref().map(|s| s.on_remote_response(io, peer_id, response));
+		self.on_demand.as_ref().map(|s| s.on_remote_call_response(io, peer_id, response));
+	}
+
+	fn on_remote_read_request(&self, io: &mut SyncIo, peer_id: PeerId, request: message::RemoteReadRequest<B::Hash>) {
+		trace!(target: "sync", "Remote read request {} from {} ({:?} at {})",
+			request.id, peer_id, request.key, request.block);
+		let proof = match self.chain.read_proof(&request.block, &request.key) {
+			Ok(proof) => proof,
+			Err(error) => {
+				trace!(target: "sync", "Remote read request {} from {} ({:?} at {}) failed with: {}",
+					request.id, peer_id, request.key, request.block, error);
+				Default::default()
+			},
+		};
+
+		self.send_message(io, peer_id, GenericMessage::RemoteReadResponse(message::RemoteReadResponse {
+			id: request.id, proof,
+		}));
+	}
+
+	fn on_remote_batch_read_request(&self, io: &mut SyncIo, peer_id: PeerId, request: message::RemoteBatchReadRequest<B::Hash>) {
+		trace!(target: "sync", "Remote batch read request {} from {} ({} keys at {})",
+			request.id, peer_id, request.keys.len(), request.block);
+		let proof = match self.chain.read_batch_proof(&request.block, &request.keys) {
+			Ok(proof) => proof,
+			Err(error) => {
+				trace!(target: "sync", "Remote batch read request {} from {} ({} keys at {}) failed with: {}",
+					request.id, peer_id, request.keys.len(), request.block, error);
+				Default::default()
+			},
+		};
+
+		self.send_message(io, peer_id, GenericMessage::RemoteReadResponse(message::RemoteReadResponse {
+			id: request.id, proof,
+		}));
+	}
+
+	fn on_remote_read_response(&self, io: &mut SyncIo, peer_id: PeerId, response: message::RemoteReadResponse) {
+		trace!(target: "sync", "Remote read response {} from {}", response.id, peer_id);
+		self.on_demand.as_ref().map(|s| s.on_remote_read_response(io, peer_id, response));
+	}
+
+	fn on_remote_header_request(&self, io: &mut SyncIo, peer_id: PeerId, request: message::RemoteHeaderRequest<B::Hash>) {
+		trace!(target: "sync", "Remote header request {} from {} ({})",
+			request.id, peer_id, request.hash);
+		let header = match self.chain.header(&BlockId::Hash(request.hash)) {
+			Ok(header) => header,
+			Err(error) => {
+				trace!(target: "sync", "Remote header request {} from {} ({}) failed with: {}",
+					request.id, peer_id, request.hash, error);
+				None
+			},
+		};
+
+		self.send_message(io, peer_id, GenericMessage::RemoteHeaderResponse(message::generic::RemoteHeaderResponse {
+			id: request.id, header,
+		}));
+	}
+
+	fn on_remote_header_response(&self, io: &mut SyncIo, peer_id: PeerId, response: message::RemoteHeaderResponse<B>) {
+		trace!(target: "sync", "Remote header response {} from {}", response.id, peer_id);
+		self.on_demand.as_ref().map(|s| s.on_remote_header_response(io, peer_id, response));
+	}
+
+	fn on_remote_cht_request(&self, io: &mut SyncIo, peer_id: PeerId, request: message::RemoteChtRequest) {
+		trace!(target: "sync", "Remote CHT request {} from {} (segment {})",
+			request.id, peer_id, request.segment);
+		let hashes = match self.chain.cht_hashes(request.segment) {
+			Ok(hashes) => Some(hashes),
+			Err(error) => {
+				trace!(target: "sync", "Remote CHT request {} from {} (segment {}) failed with: {}",
+					request.id, peer_id, request.segment, error);
+				None
+			},
+		};
+
+		self.send_message(io, peer_id, GenericMessage::RemoteChtResponse(message::generic::RemoteChtResponse {
+			id: request.id, hashes,
+		}));
+	}
+
+	fn on_remote_cht_response(&self, io: &mut SyncIo, peer_id: PeerId, response: message::RemoteChtResponse<B>) {
+		trace!(target: "sync", "Remote CHT response {} from {}", response.id, peer_id);
+		self.on_demand.as_ref().map(|s| s.on_remote_cht_response(io, peer_id, response));
 	}
 
 	pub fn chain(&self) -> &Client<B> {
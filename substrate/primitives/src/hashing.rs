@@ -17,6 +17,7 @@
 //! Hashing functions.
 
 use blake2_rfc;
+use tiny_keccak;
 use twox_hash;
 
 /// Do a Blake2 512-bit hash and place result in `dest`.
@@ -43,6 +44,18 @@ pub fn blake2_256(data: &[u8]) -> [u8; 32] {
 	r
 }
 
+/// Do a Blake2 256-bit hash of the concatenation of `data`, without concatenating it.
+pub fn blake2_256_slices(data: &[&[u8]]) -> [u8; 32] {
+	let mut hasher = blake2_rfc::blake2b::Blake2b::new(32);
+	for slice in data {
+		hasher.update(slice);
+	}
+
+	let mut r = [0; 32];
+	r.copy_from_slice(hasher.finalize().as_bytes());
+	r
+}
+
 /// Do a Blake2 128-bit hash and place result in `dest`.
 pub fn blake2_128_into(data: &[u8], dest: &mut [u8; 16]) {
 	dest.copy_from_slice(blake2_rfc::blake2b::blake2b(16, &[], data).as_bytes());
@@ -55,6 +68,18 @@ pub fn blake2_128(data: &[u8]) -> [u8; 16] {
 	r
 }
 
+/// Do a Keccak 256-bit hash and place result in `dest`.
+pub fn keccak_256_into(data: &[u8], dest: &mut [u8; 32]) {
+	tiny_keccak::Keccak::keccak256(data, dest);
+}
+
+/// Do a Keccak 256-bit hash and return result.
+pub fn keccak_256(data: &[u8]) -> [u8; 32] {
+	let mut r = [0; 32];
+	keccak_256_into(data, &mut r);
+	r
+}
+
 /// Do a XX 128-bit hash and place result in `dest`.
 pub fn twox_128_into(data: &[u8], dest: &mut [u8; 16]) {
 	use ::core::hash::Hasher;
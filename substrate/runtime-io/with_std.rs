@@ -27,7 +27,7 @@ extern crate ed25519;
 #[doc(hidden)]
 pub extern crate substrate_codec as codec;
 // re-export hashing functions.
-pub use primitives::{blake2_256, twox_128, twox_256};
+pub use primitives::{blake2_256, blake2_256_slices, keccak_256, twox_128, twox_256};
 
 pub use substrate_state_machine::{Externalities, TestExternalities};
 use primitives::hexdisplay::HexDisplay;
@@ -96,6 +96,16 @@ pub fn trie_root<
 	triehash::trie_root(input).0
 }
 
+/// A trie root formed from the iterated items, with every key hashed before insertion. Not
+/// available in the wasm build.
+pub fn sec_trie_root<
+	I: IntoIterator<Item = (A, B)>,
+	A: AsRef<[u8]> + Ord,
+	B: AsRef<[u8]>,
+>(input: I) -> [u8; 32] {
+	triehash::sec_trie_root(input).0
+}
+
 /// A trie root formed from the enumerated items.
 pub fn ordered_trie_root<
 	I: IntoIterator<Item = A>,
@@ -109,6 +119,12 @@ pub fn ed25519_verify<P: AsRef<[u8]>>(sig: &[u8; 64], msg: &[u8], pubkey: P) ->
 	ed25519::verify(sig, msg, pubkey)
 }
 
+/// Verify a batch of ed25519 signatures, each with its message and public key. `false` if any of
+/// them is invalid.
+pub fn ed25519_verify_batch(items: &[(&[u8; 64], &[u8], &[u8])]) -> bool {
+	items.iter().all(|&(sig, msg, pubkey)| ed25519::verify(sig, msg, pubkey))
+}
+
 /// Execute the given closure with global function available whose functionality routes into the
 /// externalities `ext`. Forwards the value that the closure returns.
 pub fn with_externalities<R, F: FnOnce() -> R>(ext: &mut Externalities, f: F) -> R {
@@ -62,6 +62,7 @@ extern "C" {
 	fn ext_enumerated_trie_root(values_data: *const u8, lens_data: *const u32, lens_len: u32, result: *mut u8);
 	fn ext_chain_id() -> u64;
 	fn ext_blake2_256(data: *const u8, len: u32, out: *mut u8);
+	fn ext_keccak_256(data: *const u8, len: u32, out: *mut u8);
 	fn ext_twox_128(data: *const u8, len: u32, out: *mut u8);
 	fn ext_twox_256(data: *const u8, len: u32, out: *mut u8);
 	fn ext_ed25519_verify(msg_data: *const u8, msg_len: u32, sig_data: *const u8, pubkey_data: *const u8) -> u32;
@@ -175,6 +176,24 @@ pub fn blake2_256(data: &[u8]) -> [u8; 32] {
 	result
 }
 
+/// Conduct a 256-bit Blake2 hash of the concatenation of `data`.
+pub fn blake2_256_slices(data: &[&[u8]]) -> [u8; 32] {
+	let mut buffer = Vec::new();
+	for slice in data {
+		buffer.extend_from_slice(slice);
+	}
+	blake2_256(&buffer)
+}
+
+/// Conduct a 256-bit Keccak hash.
+pub fn keccak_256(data: &[u8]) -> [u8; 32] {
+	let mut result: [u8; 32] = Default::default();
+	unsafe {
+		ext_keccak_256(data.as_ptr(), data.len() as u32, result.as_mut_ptr());
+	}
+	result
+}
+
 /// Conduct four XX hashes to give a 256-bit result.
 pub fn twox_256(data: &[u8]) -> [u8; 32] {
 	let mut result: [u8; 32] = Default::default();
@@ -200,6 +219,12 @@ pub fn ed25519_verify<P: AsRef<[u8]>>(sig: &[u8; 64], msg: &[u8], pubkey: P) ->
 	}
 }
 
+/// Verify a batch of ed25519 signatures, each with its message and public key. `false` if any of
+/// them is invalid.
+pub fn ed25519_verify_batch(items: &[(&[u8; 64], &[u8], &[u8])]) -> bool {
+	items.iter().all(|&(sig, msg, pubkey)| ed25519_verify(sig, msg, pubkey))
+}
+
 /// Trait for things which can be printed.
 pub trait Printable {
 	fn print(self);
@@ -106,8 +106,7 @@ impl<
 
 		// check transaction trie root represents the transactions.
 		let xts_root = extrinsics_root::<System::Hashing, _>(&block.extrinsics());
-		header.extrinsics_root().check_equal(&xts_root);
-		assert!(header.extrinsics_root() == &xts_root, "Transaction trie root must be valid.");
+		header.extrinsics_root().assert_equal(&xts_root).expect("Transaction trie root must be valid.");
 	}
 
 	/// Actually execute all transitioning for `block`.
@@ -205,8 +204,7 @@ impl<
 
 		// check storage root.
 		let storage_root = System::Hashing::storage_root();
-		header.state_root().check_equal(&storage_root);
-		assert!(header.state_root() == &storage_root, "Storage root must match that calculated.");
+		header.state_root().assert_equal(&storage_root).expect("Storage root must match that calculated.");
 	}
 }
 
@@ -0,0 +1,675 @@
+// Copyright 2017 Parity Technologies (UK) Ltd.
+// This file is part of Substrate Demo.
+
+// Substrate Demo is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate Demo is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate Demo.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Arithmetic helpers over `SimpleArithmetic` values.
+
+use rstd::prelude::*;
+use traits::{As, Bounded, Saturating, SimpleArithmetic, Zero};
+use Permill;
+
+/// A checked arithmetic operation.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ArithOp {
+	/// Addition.
+	Add,
+	/// Subtraction.
+	Sub,
+	/// Multiplication.
+	Mul,
+	/// Division.
+	Div,
+}
+
+/// Failure of a checked arithmetic operation, with the operation and the operands it failed on.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ArithError<N> {
+	/// The operation which failed.
+	pub op: ArithOp,
+	/// Left-hand operand.
+	pub lhs: N,
+	/// Right-hand operand.
+	pub rhs: N,
+}
+
+/// Value at the percentile `p` of the `sorted` (non-descending) slice. Linearly interpolates
+/// between the two closest samples when `p` falls between them. `None` if `sorted` is empty.
+pub fn percentile<N: SimpleArithmetic + As<u128> + Bounded + Copy>(sorted: &[N], p: Permill) -> Option<N> {
+	let last = sorted.len().checked_sub(1)?;
+	let p = ::rstd::cmp::min(p.deconstruct(), 1_000_000) as u128;
+	let position = last as u128 * p;
+	let index = (position / 1_000_000) as usize;
+	let fraction = Permill::from_millionths((position % 1_000_000) as u32);
+
+	let lower = sorted[index];
+	match sorted.get(index + 1) {
+		Some(&upper) if upper > lower => Some(lower + fraction * (upper - lower)),
+		_ => Some(lower),
+	}
+}
+
+/// Compound `principal` at `rate` for the given number of `periods`, saturating at the maximum
+/// value of `N`. This is approximate: the interest of every period is rounded down.
+pub fn compound<N>(principal: N, rate: Permill, periods: u32) -> N where
+	N: SimpleArithmetic + As<u128> + Bounded + Copy,
+{
+	let mut value = principal;
+	for _ in 0..periods {
+		value = match value.checked_add(&(rate * value)) {
+			Some(value) => value,
+			None => return N::max_value(),
+		};
+	}
+	value
+}
+
+/// `base` to the power of `exp`, modulo `modulus`, by square-and-multiply. Intermediate products
+/// are reduced at every step without overflowing `N`. Zero if `modulus` is zero.
+pub fn pow_mod<N: SimpleArithmetic + Copy>(base: N, exp: N, modulus: N) -> N {
+	if modulus.is_zero() {
+		return N::zero();
+	}
+
+	let two = N::one() + N::one();
+	let mut base = base % modulus;
+	let mut exp = exp;
+	let mut result = N::one() % modulus;
+	while !exp.is_zero() {
+		if exp % two == N::one() {
+			result = mul_mod(result, base, modulus);
+		}
+		base = mul_mod(base, base, modulus);
+		exp = exp / two;
+	}
+	result
+}
+
+/// `a * b % modulus` for `a` and `b` below `modulus`, without overflowing `N`.
+fn mul_mod<N: SimpleArithmetic + Copy>(a: N, b: N, modulus: N) -> N {
+	match a.checked_mul(&b) {
+		Some(product) => product % modulus,
+		None => multiply_by_rational_with_remainder(a, b, modulus).1,
+	}
+}
+
+/// Weighted median of `(value, weight)` samples: the smallest value at which the cumulative weight
+/// reaches half of the total weight. `None` if `samples` is empty or the total weight overflows.
+pub fn weighted_median<N: SimpleArithmetic + Copy>(samples: &[(N, N)]) -> Option<N> {
+	let mut sorted = samples.to_vec();
+	sorted.sort_by(|a, b| a.0.cmp(&b.0));
+
+	let total = sorted.iter().fold(Some(N::zero()), |total, &(_, weight)| total.and_then(|total| total.checked_add(&weight)))?;
+	let mut cumulative = N::zero();
+	for &(value, weight) in &sorted {
+		cumulative = cumulative.checked_add(&weight)?;
+		if cumulative >= total - cumulative {
+			return Some(value);
+		}
+	}
+	None
+}
+
+/// Sum of `items`, or `None` if it overflows.
+pub fn checked_sum<N: SimpleArithmetic + Copy>(items: &[N]) -> Option<N> {
+	items.iter().fold(Some(N::zero()), |sum, item| sum.and_then(|sum| sum.checked_add(item)))
+}
+
+/// Geometric mean of `samples`, rounded down. `None` if `samples` is empty or any of them is zero.
+///
+/// The samples are widened to `u128`, so this is exact while their product fits in it. Otherwise
+/// all samples are halved (but kept at least one) until it does and the result is scaled back,
+/// losing the halved-off low bits.
+pub fn geometric_mean<N: SimpleArithmetic + As<u128> + Copy>(samples: &[N]) -> Option<N> {
+	if samples.is_empty() || samples.iter().any(Zero::is_zero) {
+		return None;
+	}
+
+	let mut scaled: Vec<u128> = samples.iter().map(|sample| sample.as_()).collect();
+	let max = scaled.iter().cloned().max().unwrap_or(0);
+	let mut scale = 1u128;
+	let product = loop {
+		if let Some(product) = checked_product(&scaled) {
+			break product;
+		}
+
+		for sample in scaled.iter_mut() {
+			*sample = if *sample > 1 { *sample / 2 } else { 1 };
+		}
+		scale *= 2;
+	};
+
+	let mean = nth_root(product, samples.len()).checked_mul(scale).unwrap_or(max);
+	Some(N::sa(::rstd::cmp::min(mean, max)))
+}
+
+/// Product of `items`, or `None` if it overflows.
+fn checked_product<N: SimpleArithmetic + Copy>(items: &[N]) -> Option<N> {
+	items.iter().fold(Some(N::one()), |product, item| product.and_then(|product| product.checked_mul(item)))
+}
+
+/// Largest `root` with `root^n <= value`, for a positive `n`.
+fn nth_root<N: SimpleArithmetic + Copy>(value: N, n: usize) -> N {
+	match n {
+		1 => return value,
+		2 => return value.integer_sqrt(),
+		_ => (),
+	}
+
+	// the root is at most the square root, so narrow it down from there.
+	let pow_fits = |root: N| (0..n).fold(Some(N::one()), |pow, _| pow
+		.and_then(|pow| pow.checked_mul(&root))
+		.and_then(|pow| if pow > value { None } else { Some(pow) })
+	).is_some();
+	let (mut lo, mut hi) = (N::one(), value.integer_sqrt());
+	while lo < hi {
+		let mid = hi - (hi - lo) / (N::one() + N::one());
+		if pow_fits(mid) { lo = mid; } else { hi = mid - N::one(); }
+	}
+	lo
+}
+
+/// Average of `(value, duration)` samples, weighted by their durations and rounded down. `None` if
+/// `samples` is empty or the total duration is zero.
+///
+/// Every sample contributes `value * duration / total` with the remainders carried over, so this
+/// is exact without ever forming the full products. If the total duration overflows `N`, all
+/// durations are halved until it fits, which may drop the shortest samples.
+pub fn time_weighted_average<N: SimpleArithmetic + Copy>(samples: &[(N, N)]) -> Option<N> {
+	let two = N::one() + N::one();
+	let mut durations: Vec<N> = samples.iter().map(|&(_, duration)| duration).collect();
+	let total = loop {
+		if let Some(total) = checked_sum(&durations) {
+			break total;
+		}
+
+		for duration in durations.iter_mut() {
+			*duration = *duration / two;
+		}
+	};
+	if total.is_zero() {
+		return None;
+	}
+
+	let (mut average, mut remainder) = (N::zero(), N::zero());
+	for (&(value, _), &duration) in samples.iter().zip(durations.iter()) {
+		let (share, share_remainder) = multiply_by_rational_with_remainder(value, duration, total);
+		average += share;
+		let (sum, carry) = add_mod(remainder, share_remainder, total);
+		remainder = sum;
+		if carry {
+			average += N::one();
+		}
+	}
+	Some(average)
+}
+
+/// Sum of the amounts added over the last `window` blocks, keeping at most a fixed number of
+/// `(block, amount)` entries. Entries are evicted once they fall out of the window; adding to a
+/// full window sum fails rather than dropping entries which are still inside it.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct SlidingWindowSum<B, N> {
+	entries: Vec<(B, N)>,
+	capacity: usize,
+	window: B,
+}
+
+impl<B: SimpleArithmetic + Copy, N: SimpleArithmetic + Bounded + Copy> SlidingWindowSum<B, N> {
+	/// Create an empty sum over the last `window` blocks, keeping at most `capacity` entries.
+	pub fn new(capacity: usize, window: B) -> Self {
+		SlidingWindowSum {
+			entries: Vec::with_capacity(capacity),
+			capacity,
+			window,
+		}
+	}
+
+	/// Record `amount` at `block`, evicting the entries which are out of the window at `block`.
+	/// Fails if all kept entries are still inside the window.
+	pub fn add(&mut self, block: B, amount: N) -> Result<(), &'static str> {
+		let window = self.window;
+		self.entries.retain(|&(kept, _)| kept > block || block - kept < window);
+		if self.entries.len() >= self.capacity {
+			return Err("sliding window sum is full");
+		}
+
+		self.entries.push((block, amount));
+		Ok(())
+	}
+
+	/// Sum of the amounts added within the window up to and including `now`, saturating at the
+	/// maximum value of `N`.
+	pub fn window_sum(&self, now: B) -> N {
+		self.entries.iter()
+			.filter(|&&(block, _)| block <= now && now - block < self.window)
+			.fold(N::zero(), |sum, &(_, amount)| sum.saturating_add(amount))
+	}
+}
+
+/// Map `x` from the range `[in_lo, in_hi]` onto `[out_lo, out_hi]`, rounding towards `out_lo`.
+/// `x` is clamped into the input range first, and an empty input range maps onto `out_lo`. The
+/// output range may be descending. The scaling can't overflow as long as both ranges fit in `N`.
+pub fn rescale<N: SimpleArithmetic + Copy>(x: N, in_lo: N, in_hi: N, out_lo: N, out_hi: N) -> N {
+	if in_hi <= in_lo {
+		return out_lo;
+	}
+
+	let x = if x < in_lo { in_lo } else if x > in_hi { in_hi } else { x };
+	let (offset, span) = (x - in_lo, in_hi - in_lo);
+	if out_hi >= out_lo {
+		out_lo + multiply_by_rational(out_hi - out_lo, offset, span)
+	} else {
+		out_lo - multiply_by_rational(out_lo - out_hi, offset, span)
+	}
+}
+
+/// `value * numerator / denominator`, rounded down, for `numerator <= denominator` and non-zero
+/// `denominator`, without overflowing `N`.
+fn multiply_by_rational<N: SimpleArithmetic + Copy>(value: N, numerator: N, denominator: N) -> N {
+	multiply_by_rational_with_remainder(value, numerator, denominator).0
+}
+
+/// Like `multiply_by_rational`, also returning the remainder `value * numerator % denominator`.
+fn multiply_by_rational_with_remainder<N: SimpleArithmetic + Copy>(value: N, numerator: N, denominator: N) -> (N, N) {
+	// value = whole * denominator + rest, so the result is whole * numerator plus the fraction
+	// `rest * numerator / denominator`, with both factors of the fraction below `denominator`.
+	let whole = value / denominator;
+	let rest = value % denominator;
+	if numerator == denominator {
+		return (value, N::zero());
+	}
+
+	// long multiplication of `rest` by the bits of `numerator`, tracking every partial product as a
+	// multiple of `denominator` plus a remainder below it.
+	let two = N::one() + N::one();
+	let (mut quotient, mut remainder) = (N::zero(), N::zero());
+	let (mut term_quotient, mut term_remainder) = (N::zero(), rest);
+	let mut bits = numerator;
+	loop {
+		if bits % two == N::one() {
+			quotient += term_quotient;
+			let (sum, carry) = add_mod(remainder, term_remainder, denominator);
+			remainder = sum;
+			if carry {
+				quotient += N::one();
+			}
+		}
+
+		bits = bits / two;
+		if bits.is_zero() {
+			break;
+		}
+
+		term_quotient += term_quotient;
+		let (doubled, carry) = add_mod(term_remainder, term_remainder, denominator);
+		term_remainder = doubled;
+		if carry {
+			term_quotient += N::one();
+		}
+	}
+
+	(whole * numerator + quotient, remainder)
+}
+
+/// `(a + b) % modulus` and whether the sum wrapped, for `a` and `b` below `modulus`.
+fn add_mod<N: SimpleArithmetic + Copy>(a: N, b: N, modulus: N) -> (N, bool) {
+	let gap = modulus - b;
+	if a >= gap {
+		(a - gap, true)
+	} else {
+		(a + b, false)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn percentile_of_odd_length_slice() {
+		assert_eq!(percentile(&[1u64, 2, 3, 4, 5], Permill::from_percent(50)), Some(3));
+		assert_eq!(percentile(&[1u64, 2, 3, 4, 5], Permill::from_percent(25)), Some(2));
+		assert_eq!(percentile(&[1u64, 2, 3, 4, 5], Permill::from_percent(100)), Some(5));
+	}
+
+	#[test]
+	fn percentile_of_even_length_slice_interpolates() {
+		assert_eq!(percentile(&[10u64, 20, 30, 40], Permill::from_percent(50)), Some(25));
+		assert_eq!(percentile(&[10u64, 20, 30, 40], Permill::from_percent(0)), Some(10));
+	}
+
+	#[test]
+	fn percentile_of_empty_slice_is_none() {
+		assert_eq!(percentile::<u64>(&[], Permill::from_percent(50)), None);
+	}
+
+	#[test]
+	fn percentile_of_wide_values() {
+		assert_eq!(percentile(&[0u128, 1 << 100], Permill::from_percent(50)), Some(1 << 99));
+		assert_eq!(percentile(&[1u128 << 100, u128::max_value()], Permill::from_percent(100)), Some(u128::max_value()));
+	}
+
+	#[test]
+	fn compound_over_zero_periods_is_principal() {
+		assert_eq!(compound(1_000u64, Permill::from_percent(10), 0), 1_000);
+	}
+
+	#[test]
+	fn compound_follows_reference_computation() {
+		let principal = 1_000_000_000u64;
+		let reference = principal as f64 * 1.05f64.powi(10);
+		let result = compound(principal, Permill::from_percent(5), 10);
+		assert!(result as f64 <= reference);
+		assert!(reference - result as f64 <= 10.0);
+		assert_eq!(compound(1_000_000u64, Permill::from_percent(10), 3), 1_331_000);
+	}
+
+	#[test]
+	fn compound_saturates() {
+		assert_eq!(compound(u32::max_value() / 2, Permill::from_percent(100), 2), u32::max_value());
+	}
+
+	#[test]
+	fn compound_of_wide_values() {
+		let principal = 1u128 << 100;
+		assert_eq!(compound(principal, Permill::from_percent(10), 1), principal + principal / 10);
+		assert_eq!(compound(u128::max_value() / 2, Permill::from_percent(100), 2), u128::max_value());
+	}
+
+	#[test]
+	fn pow_mod_works() {
+		assert_eq!(pow_mod(4u64, 13, 497), 445);
+		assert_eq!(pow_mod(2u64, 10, 1_000), 24);
+		assert_eq!(pow_mod(7u32, 0, 13), 1);
+		assert_eq!(pow_mod(7u32, 3, 1), 0);
+	}
+
+	#[test]
+	fn pow_mod_with_large_exponent() {
+		// Fermat's little theorem: 3^(p - 1) = 1 (mod p) for prime p.
+		assert_eq!(pow_mod(3u64, 1_000_000_006, 1_000_000_007), 1);
+		assert_eq!(pow_mod(u64::max_value() - 1, u64::max_value(), u64::max_value()), u64::max_value() - 1);
+	}
+
+	#[test]
+	fn pow_mod_of_wide_values() {
+		let max = u128::max_value();
+		assert_eq!(pow_mod(2u128, 127, max), 1 << 127);
+		// max - 1 is -1 modulo max.
+		assert_eq!(pow_mod(max - 1, 2, max), 1);
+		assert_eq!(pow_mod(max - 1, max, max), max - 1);
+		assert_eq!(pow_mod(1u128 << 100, 2, (1 << 127) + 1), (1 << 127) + 1 - (1 << 73));
+	}
+
+	#[test]
+	fn pow_mod_with_zero_modulus_is_zero() {
+		assert_eq!(pow_mod(3u64, 5, 0), 0);
+	}
+
+	#[test]
+	fn weighted_median_follows_weights() {
+		// unweighted median would be 2 and 20.
+		assert_eq!(weighted_median(&[(1u64, 1), (2, 1), (3, 10)]), Some(3));
+		assert_eq!(weighted_median(&[(40u64, 1), (10, 5), (30, 1), (20, 1)]), Some(10));
+		assert_eq!(weighted_median(&[(1u64, 1), (2, 1), (3, 1)]), Some(2));
+	}
+
+	#[test]
+	fn weighted_median_of_no_samples_is_none() {
+		assert_eq!(weighted_median::<u64>(&[]), None);
+	}
+
+	#[test]
+	fn weighted_median_handles_large_weights() {
+		let half = u64::max_value() / 2;
+		assert_eq!(weighted_median(&[(1u64, half), (2, 1), (3, half)]), Some(2));
+		assert_eq!(weighted_median(&[(1u64, half), (2, half + 1)]), Some(2));
+		assert_eq!(weighted_median(&[(1u64, half), (2, half), (3, 2)]), None);
+	}
+
+	#[test]
+	fn checked_op_works() {
+		assert_eq!(u32::checked_op(6, ArithOp::Add, 3), Ok(9));
+		assert_eq!(u32::checked_op(6, ArithOp::Sub, 3), Ok(3));
+		assert_eq!(u32::checked_op(6, ArithOp::Mul, 3), Ok(18));
+		assert_eq!(u32::checked_op(6, ArithOp::Div, 3), Ok(2));
+	}
+
+	#[test]
+	fn checked_op_reports_overflow() {
+		assert_eq!(u32::checked_op(u32::max_value(), ArithOp::Add, 1), Err(ArithError { op: ArithOp::Add, lhs: u32::max_value(), rhs: 1 }));
+		assert_eq!(u32::checked_op(1 << 16, ArithOp::Mul, 1 << 16), Err(ArithError { op: ArithOp::Mul, lhs: 1 << 16, rhs: 1 << 16 }));
+	}
+
+	#[test]
+	fn checked_op_reports_underflow() {
+		assert_eq!(u64::checked_op(1, ArithOp::Sub, 2), Err(ArithError { op: ArithOp::Sub, lhs: 1, rhs: 2 }));
+	}
+
+	#[test]
+	fn checked_op_reports_division_by_zero() {
+		assert_eq!(u64::checked_op(1, ArithOp::Div, 0), Err(ArithError { op: ArithOp::Div, lhs: 1, rhs: 0 }));
+	}
+
+	#[test]
+	fn rescale_maps_endpoints_and_midpoint() {
+		assert_eq!(rescale(10u32, 10, 20, 100, 200), 100);
+		assert_eq!(rescale(20u32, 10, 20, 100, 200), 200);
+		assert_eq!(rescale(15u32, 10, 20, 100, 200), 150);
+		assert_eq!(rescale(15u32, 10, 20, 200, 100), 150);
+		assert_eq!(rescale(12u32, 10, 20, 0, 3), 0);
+	}
+
+	#[test]
+	fn rescale_clamps_out_of_range_input() {
+		assert_eq!(rescale(5u32, 10, 20, 100, 200), 100);
+		assert_eq!(rescale(25u32, 10, 20, 100, 200), 200);
+		assert_eq!(rescale(25u32, 20, 20, 100, 200), 100);
+	}
+
+	#[test]
+	fn rescale_does_not_overflow() {
+		let max = u64::max_value();
+		assert_eq!(rescale(max / 2, 0, max, 0, max), max / 2);
+		assert_eq!(rescale(max - 1, 0, max, 0, max - 2), max - 3);
+		assert_eq!(rescale(3u64, 0, 4, 0, max), ((max as u128) * 3 / 4) as u64);
+	}
+
+	#[test]
+	fn div_round_even_rounds_halves_to_even() {
+		assert_eq!(5u32.div_round_even(2), 2);
+		assert_eq!(7u32.div_round_even(2), 4);
+		assert_eq!(1u32.div_round_even(2), 0);
+		assert_eq!(15u64.div_round_even(10), 2);
+		assert_eq!(25u64.div_round_even(10), 2);
+	}
+
+	#[test]
+	fn div_round_even_rounds_to_nearest() {
+		assert_eq!(7u32.div_round_even(3), 2);
+		assert_eq!(8u32.div_round_even(3), 3);
+		assert_eq!(9u32.div_round_even(3), 3);
+		assert_eq!(u64::max_value().div_round_even(u64::max_value() - 1), 1);
+	}
+
+	#[test]
+	fn checked_sum_works() {
+		assert_eq!(checked_sum(&[1u64, 2, 3]), Some(6));
+		assert_eq!(checked_sum::<u64>(&[]), Some(0));
+		assert_eq!(checked_sum(&[u32::max_value() - 3, 3]), Some(u32::max_value()));
+	}
+
+	#[test]
+	fn checked_sum_detects_overflow_partway() {
+		// the total would fit again after the overflow if wrapping was allowed.
+		assert_eq!(checked_sum(&[u32::max_value(), 1, u32::max_value()]), None);
+		assert_eq!(checked_sum(&[u64::max_value() / 2, u64::max_value() / 2, 2, 0]), None);
+	}
+
+	#[test]
+	fn accumulate_capped_clamps_at_cap() {
+		let mut total = 0u64;
+		assert_eq!(total.accumulate_capped(40, 100), 40);
+		assert_eq!(total.accumulate_capped(40, 100), 40);
+		assert_eq!(total.accumulate_capped(40, 100), 20);
+		assert_eq!(total, 100);
+		assert_eq!(total.accumulate_capped(40, 100), 0);
+		assert_eq!(total, 100);
+	}
+
+	#[test]
+	fn accumulate_capped_does_not_overflow() {
+		let mut total = u32::max_value() - 5;
+		assert_eq!(total.accumulate_capped(u32::max_value(), u32::max_value()), 5);
+		assert_eq!(total, u32::max_value());
+
+		let mut above = 150u32;
+		assert_eq!(above.accumulate_capped(10, 100), 0);
+		assert_eq!(above, 150);
+	}
+
+	#[test]
+	fn geometric_mean_of_small_sets() {
+		assert_eq!(geometric_mean(&[2u64, 8]), Some(4));
+		assert_eq!(geometric_mean(&[1u64, 3, 9]), Some(3));
+		assert_eq!(geometric_mean(&[2u32, 4, 8, 16, 32]), Some(8));
+		assert_eq!(geometric_mean(&[7u32]), Some(7));
+		// sqrt(2) and cbrt(30) round down.
+		assert_eq!(geometric_mean(&[1u64, 2]), Some(1));
+		assert_eq!(geometric_mean(&[2u64, 3, 5]), Some(3));
+	}
+
+	#[test]
+	fn geometric_mean_of_empty_or_zero_is_none() {
+		assert_eq!(geometric_mean::<u64>(&[]), None);
+		assert_eq!(geometric_mean(&[4u64, 0, 9]), None);
+	}
+
+	#[test]
+	fn geometric_mean_with_overflowing_product() {
+		assert_eq!(geometric_mean(&[1u64 << 40, 1 << 40, 1 << 40]), Some(1 << 40));
+		assert_eq!(geometric_mean(&[u32::max_value(), u32::max_value()]), Some(u32::max_value()));
+		// halved down to 64 bits, so the low 64 bits are lost.
+		assert_eq!(geometric_mean(&[u128::max_value(), u128::max_value()]), Some(u128::max_value() - u64::max_value() as u128));
+	}
+
+	#[test]
+	fn time_weighted_average_of_equal_durations_is_mean() {
+		assert_eq!(time_weighted_average(&[(10u64, 5), (20, 5), (60, 5)]), Some(30));
+		assert_eq!(time_weighted_average(&[(1u32, 3), (2, 3)]), Some(1));
+		assert_eq!(time_weighted_average(&[(42u32, 1)]), Some(42));
+	}
+
+	#[test]
+	fn time_weighted_average_follows_durations() {
+		assert_eq!(time_weighted_average(&[(10u64, 1), (20, 3)]), Some(17));
+		assert_eq!(time_weighted_average(&[(10u64, 3), (20, 1)]), Some(12));
+		assert_eq!(time_weighted_average(&[(10u64, 0), (20, 7)]), Some(20));
+	}
+
+	#[test]
+	fn time_weighted_average_of_no_time_is_none() {
+		assert_eq!(time_weighted_average::<u64>(&[]), None);
+		assert_eq!(time_weighted_average(&[(10u64, 0), (20, 0)]), None);
+	}
+
+	#[test]
+	fn time_weighted_average_does_not_overflow() {
+		let max = u64::max_value();
+		assert_eq!(time_weighted_average(&[(max, max / 2), (max - 2, max / 2)]), Some(max - 1));
+		assert_eq!(time_weighted_average(&[(max, max), (max, max)]), Some(max));
+	}
+
+	#[test]
+	fn window_sum_excludes_entries_outside_window() {
+		let sum_over = |window| {
+			let mut sum = SlidingWindowSum::<u64, u32>::new(8, window);
+			sum.add(1, 10).unwrap();
+			sum.add(5, 20).unwrap();
+			sum.add(9, 40).unwrap();
+			sum.window_sum(9)
+		};
+		assert_eq!(sum_over(5), 60);
+		assert_eq!(sum_over(9), 70);
+		assert_eq!(sum_over(1), 40);
+		assert_eq!(sum_over(0), 0);
+
+		// entries after `now` don't count yet.
+		let mut sum = SlidingWindowSum::<u64, u32>::new(8, 10);
+		sum.add(1, 10).unwrap();
+		sum.add(5, 20).unwrap();
+		sum.add(9, 40).unwrap();
+		assert_eq!(sum.window_sum(6), 30);
+	}
+
+	#[test]
+	fn window_sum_evicts_entries_out_of_window() {
+		let mut sum = SlidingWindowSum::<u64, u32>::new(3, 3);
+		for block in 1..4 {
+			sum.add(block, block as u32).unwrap();
+		}
+		assert_eq!(sum.window_sum(3), 6);
+
+		// every added block evicts the one falling out of the window.
+		for block in 4..10 {
+			sum.add(block, block as u32).unwrap();
+			assert_eq!(sum.window_sum(block), 3 * block as u32 - 3);
+		}
+		assert_eq!(sum.window_sum(11), 9);
+	}
+
+	#[test]
+	fn window_sum_rejects_entries_when_full() {
+		let mut sum = SlidingWindowSum::<u64, u32>::new(2, 10);
+		sum.add(1, 1).unwrap();
+		sum.add(2, 2).unwrap();
+		assert!(sum.add(3, 3).is_err());
+		assert_eq!(sum.window_sum(3), 3);
+
+		// block #1 is out of the window at #11, making room.
+		sum.add(11, 5).unwrap();
+		assert_eq!(sum.window_sum(11), 7);
+
+		let mut empty = SlidingWindowSum::<u64, u32>::new(0, 10);
+		assert!(empty.add(1, 10).is_err());
+		assert_eq!(empty.window_sum(1), 0);
+	}
+
+	#[test]
+	fn window_sum_saturates() {
+		let mut sum = SlidingWindowSum::<u64, u32>::new(2, 10);
+		sum.add(1, u32::max_value()).unwrap();
+		sum.add(2, 1).unwrap();
+		assert_eq!(sum.window_sum(2), u32::max_value());
+	}
+
+	#[test]
+	fn geometric_mean_of_pair_rounds_down() {
+		assert_eq!(geometric_mean(&[4u64, 9]), Some(6));
+		assert_eq!(geometric_mean(&[2u32, 8]), Some(4));
+		assert_eq!(geometric_mean(&[2u64, 3]), Some(2));
+	}
+
+	#[test]
+	fn geometric_mean_of_pair_does_not_overflow() {
+		let max = u64::max_value();
+		assert_eq!(geometric_mean(&[max, max]), Some(max));
+		assert_eq!(geometric_mean(&[1u64 << 40, 1 << 50]), Some(1 << 45));
+		assert_eq!(geometric_mean(&[max, 1]), Some((1u64 << 32) - 1));
+		assert_eq!(geometric_mean(&[1u128 << 70, 1 << 70]), Some(1 << 70));
+	}
+}
@@ -0,0 +1,129 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Balance newtype, so that balances can't be mixed up with other numerics.
+
+use rstd::ops::{Add, Sub, Mul, Div, Rem, AddAssign, SubAssign, MulAssign, DivAssign, RemAssign};
+use codec::{Slicable, Input};
+use traits::{As, Bounded, CheckedAdd, CheckedSub, CheckedMul, CheckedDiv, IntegerSquareRoot, One, Zero};
+
+/// An amount of currency. Behaves like the `u128` it wraps, but only combines with other balances.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Default)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct Balance(pub u128);
+
+macro_rules! impl_balance_ops {
+	( $( $op:ident :: $f:ident, $assign_op:ident :: $assign_f:ident, $checked_op:ident :: $checked_f:ident; )* ) => {
+		$(
+			impl $op for Balance {
+				type Output = Balance;
+				fn $f(self, other: Balance) -> Balance { Balance(self.0.$f(other.0)) }
+			}
+			impl $assign_op for Balance {
+				fn $assign_f(&mut self, other: Balance) { self.0.$assign_f(other.0) }
+			}
+			impl $checked_op for Balance {
+				fn $checked_f(&self, other: &Balance) -> Option<Balance> { self.0.$checked_f(other.0).map(Balance) }
+			}
+		)*
+	};
+}
+
+impl_balance_ops! {
+	Add::add, AddAssign::add_assign, CheckedAdd::checked_add;
+	Sub::sub, SubAssign::sub_assign, CheckedSub::checked_sub;
+	Mul::mul, MulAssign::mul_assign, CheckedMul::checked_mul;
+	Div::div, DivAssign::div_assign, CheckedDiv::checked_div;
+}
+
+impl Rem for Balance {
+	type Output = Balance;
+	fn rem(self, other: Balance) -> Balance { Balance(self.0 % other.0) }
+}
+
+impl RemAssign for Balance {
+	fn rem_assign(&mut self, other: Balance) { self.0 %= other.0 }
+}
+
+impl Zero for Balance {
+	fn zero() -> Balance { Balance(0) }
+	fn is_zero(&self) -> bool { self.0 == 0 }
+}
+
+impl One for Balance {
+	fn one() -> Balance { Balance(1) }
+}
+
+impl Bounded for Balance {
+	fn min_value() -> Balance { Balance(u128::min_value()) }
+	fn max_value() -> Balance { Balance(u128::max_value()) }
+}
+
+impl IntegerSquareRoot for Balance {
+	fn integer_sqrt_checked(&self) -> Option<Balance> { self.0.integer_sqrt_checked().map(Balance) }
+}
+
+impl As<usize> for Balance {
+	// saturating, as `usize` may be narrower than a balance.
+	fn as_(self) -> usize { ::rstd::cmp::min(self.0, usize::max_value() as u128) as usize }
+	fn sa(n: usize) -> Balance { Balance(n as u128) }
+}
+
+impl As<u128> for Balance {
+	fn as_(self) -> u128 { self.0 }
+	fn sa(n: u128) -> Balance { Balance(n) }
+}
+
+impl Slicable for Balance {
+	fn decode<I: Input>(input: &mut I) -> Option<Self> { Some(Balance(Slicable::decode(input)?)) }
+	fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R { self.0.using_encoded(f) }
+}
+
+#[cfg(test)]
+mod tests {
+	use codec::Slicable;
+	use traits::{As, CheckedAdd, SimpleArithmetic};
+	use super::Balance;
+
+	fn total<N: SimpleArithmetic + Copy>(amounts: &[N]) -> Option<N> {
+		amounts.iter().fold(Some(N::zero()), |total, amount| total.and_then(|total| total.checked_add(amount)))
+	}
+
+	#[test]
+	fn balance_is_simple_arithmetic() {
+		assert_eq!(total(&[Balance(1), Balance(2), Balance(3)]), Some(Balance(6)));
+		assert_eq!(total(&[Balance(u128::max_value()), Balance(1)]), None);
+		assert_eq!(Balance(7).div_round_even(Balance(2)), Balance(4));
+		assert_eq!(Balance(17) % Balance(5) * Balance(3) - Balance(1), Balance(5));
+		assert_eq!(Balance(1u128 << 100).checked_add(&Balance(1)), Some(Balance((1u128 << 100) + 1)));
+	}
+
+	#[test]
+	fn balance_converts_through_u128() {
+		assert_eq!(<Balance as As<u128>>::sa(u128::max_value()).0, u128::max_value());
+		assert_eq!(<Balance as As<u128>>::as_(Balance(5)), 5u128);
+		assert_eq!(<Balance as As<usize>>::as_(Balance(5)), 5usize);
+		assert_eq!(<Balance as As<usize>>::as_(Balance(u128::max_value())), usize::max_value());
+	}
+
+	#[test]
+	fn balance_codec_roundtrip() {
+		let balance = Balance((1u128 << 100) + 42);
+		let encoded = balance.encode();
+		assert_eq!(encoded, ((1u128 << 100) + 42).encode());
+		assert_eq!(Balance::decode(&mut &encoded[..]), Some(balance));
+	}
+}
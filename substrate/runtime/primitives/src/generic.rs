@@ -133,7 +133,7 @@ where
 			if ::verify_encoded_lazy(&self.signature, &extrinsic, &extrinsic.signed) {
 				Ok(CheckedExtrinsic(extrinsic))
 			} else {
-				Err("bad signature in extrinsic")
+				Err(::RuntimeError::BadSignature.into())
 			}
 		}
 	}
@@ -236,6 +236,43 @@ pub struct Digest<Item> {
 	pub logs: Vec<Item>,
 }
 
+impl<Item: Slicable> Digest<Item> {
+	/// The set of kinds of the logs, to keep around for looking up logs by kind without going
+	/// through them when there is none of a kind.
+	pub fn kinds(&self) -> DigestKinds {
+		let mut kinds = DigestKinds::default();
+		for log in &self.logs {
+			kinds.note(log);
+		}
+		kinds
+	}
+}
+
+impl<Item> From<Vec<Item>> for Digest<Item> {
+	fn from(logs: Vec<Item>) -> Self {
+		Digest { logs }
+	}
+}
+
+/// Set of the kinds of the logs of a `Digest`. The kind of a log is the first byte of its
+/// encoding, i.e. the variant index of an enum log.
+#[derive(Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct DigestKinds([u64; 4]);
+
+impl DigestKinds {
+	/// Add the kind of `log` to the set.
+	pub fn note<Item: Slicable>(&mut self, log: &Item) {
+		let kind = log.using_encoded(|encoded| encoded.first().cloned().unwrap_or(0));
+		self.0[kind as usize / 64] |= 1 << (kind % 64);
+	}
+
+	/// Whether there may be a log of given kind. There is none if this is `false`.
+	pub fn might_contain(&self, kind: u8) -> bool {
+		self.0[kind as usize / 64] & (1 << (kind % 64)) != 0
+	}
+}
+
 impl<Item> Slicable for Digest<Item> where
 	Item: Member + Default + Slicable
 {
@@ -253,6 +290,16 @@ impl<Item> traits::Digest for Digest<Item> where
 	fn push(&mut self, item: Self::Item) {
 		self.logs.push(item);
 	}
+	fn logs(&self) -> &[Self::Item] {
+		&self.logs
+	}
+}
+impl<Item, Number> traits::NumberDigest<Number> for Digest<Item> where
+	Item: traits::NumberLog<Number>
+{
+	fn number_log(&self) -> Option<Number> {
+		self.logs.iter().filter_map(|log| traits::NumberLog::<Number>::as_number(log)).next()
+	}
 }
 
 
@@ -478,15 +525,30 @@ where
 
 #[cfg(test)]
 mod tests {
-	use codec::Slicable;
+	use codec::{Slicable, Input};
 	use substrate_primitives::{H256, H512};
-	use super::{Digest, Header, UncheckedExtrinsic, Extrinsic};
+	use traits::{BlakeTwo256, Block as BlockT, Digest as DigestT, Hashing, Header as HeaderT, Keccak256, NumberLog};
+	use super::{Digest, DigestKinds, Header, UncheckedExtrinsic, Extrinsic};
+
+	type TestHeader = Header<u64, ::traits::BlakeTwo256, Vec<u8>>;
 
 	type Block = super::Block<
-		Header<u64, ::traits::BlakeTwo256, Vec<u8>>,
+		TestHeader,
 		UncheckedExtrinsic<H256, u64, u64, ::Ed25519Signature>,
 	>;
 
+	#[test]
+	fn header_roundtrips_through_codec() {
+		::testing::assert_header_roundtrip(<TestHeader as HeaderT>::new(
+			100_000,
+			[2u8; 32].into(),
+			[1u8; 32].into(),
+			[0u8; 32].into(),
+			Digest::from(vec![vec![1, 2, 3], vec![], vec![4, 5, 6]]),
+		));
+		::testing::assert_header_roundtrip(<TestHeader as HeaderT>::new(0, Default::default(), Default::default(), Default::default(), Default::default()));
+	}
+
 	#[test]
 	fn block_roundtrip_serialization() {
 		let block: Block = Block {
@@ -530,4 +592,172 @@ mod tests {
 			assert_eq!(block, decoded);
 		}
 	}
+
+	/// Log which records the block number when its kind is `1`.
+	#[derive(PartialEq, Eq, Clone, Default, Serialize, Deserialize, Debug)]
+	struct TestLog(u8, u64);
+
+	impl Slicable for TestLog {
+		fn decode<I: Input>(input: &mut I) -> Option<Self> {
+			Some(TestLog(Slicable::decode(input)?, Slicable::decode(input)?))
+		}
+		fn encode(&self) -> Vec<u8> {
+			(self.0, self.1).encode()
+		}
+	}
+
+	impl NumberLog<u64> for TestLog {
+		fn as_number(&self) -> Option<u64> {
+			match self.0 {
+				1 => Some(self.1),
+				_ => None,
+			}
+		}
+	}
+
+	fn block_with_logs(number: u64, logs: Vec<TestLog>) -> super::Block<Header<u64, ::traits::BlakeTwo256, TestLog>, u64> {
+		super::Block {
+			header: Header {
+				parent_hash: Default::default(),
+				number,
+				state_root: Default::default(),
+				extrinsics_root: Default::default(),
+				digest: Digest { logs },
+			},
+			extrinsics: vec![],
+		}
+	}
+
+	#[test]
+	fn number_log_matching_header_is_accepted() {
+		let block = block_with_logs(42, vec![TestLog(0, 7), TestLog(1, 42)]);
+		assert!(block.check_number_log());
+	}
+
+	#[test]
+	fn number_log_mismatching_header_is_rejected() {
+		let block = block_with_logs(42, vec![TestLog(1, 41)]);
+		assert!(!block.check_number_log());
+	}
+
+	#[test]
+	fn missing_number_log_is_accepted() {
+		let block = block_with_logs(42, vec![TestLog(0, 41)]);
+		assert!(block.check_number_log());
+	}
+
+	#[test]
+	fn digest_log_is_found_by_predicate() {
+		let mut digest = Digest::default();
+		digest.push(TestLog(0, 7));
+		digest.push(TestLog(1, 42));
+		digest.push(TestLog(2, 9));
+		digest.push(TestLog(1, 43));
+
+		assert_eq!(digest.logs().len(), 4);
+		assert_eq!(digest.find(|log| if log.0 == 1 { Some(&log.1) } else { None }), Some(&42));
+		assert_eq!(digest.find(|log| if log.0 == 3 { Some(&log.1) } else { None }), None);
+	}
+
+	#[test]
+	fn digest_knows_kinds_of_its_logs() {
+		// random log sets, from a fixed-seed linear congruential generator.
+		let mut seed = 42u64;
+		let mut next = || { seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407); seed >> 33 };
+		for _ in 0..100 {
+			let logs: Vec<_> = (0..next() % 16).map(|_| TestLog(next() as u8, next())).collect();
+			let mut pushed = Digest::default();
+			let mut noted = DigestKinds::default();
+			for log in &logs {
+				pushed.push(log.clone());
+				noted.note(log);
+			}
+			let decoded: Digest<TestLog> = Slicable::decode(&mut &pushed.encode()[..]).unwrap();
+			assert_eq!(pushed.kinds(), noted);
+			assert_eq!(decoded.kinds(), noted);
+
+			for kind in 0..256u32 {
+				let kind = kind as u8;
+				let present = logs.iter().any(|log| log.0 == kind);
+				assert!(!present || noted.might_contain(kind));
+				let found = decoded.find(|log| if log.0 == kind { Some(&log.1) } else { None });
+				assert_eq!(found, logs.iter().find(|log| log.0 == kind).map(|log| &log.1));
+			}
+		}
+	}
+
+	#[test]
+	fn digest_kinds_rule_out_absent_kinds() {
+		let kinds = Digest { logs: vec![TestLog(3, 7), TestLog(200, 9)] }.kinds();
+		assert!(kinds.might_contain(3) && kinds.might_contain(200));
+		assert!(!kinds.might_contain(1));
+		assert!(!Digest::<TestLog>::default().kinds().might_contain(0));
+	}
+
+	#[test]
+	fn non_genesis_header_with_roots_is_plausible() {
+		let header: TestHeader = HeaderT::new(1, [1u8; 32].into(), [2u8; 32].into(), Default::default(), Default::default());
+		assert!(header.has_plausible_roots());
+
+		let genesis: TestHeader = HeaderT::new(0, Default::default(), Default::default(), Default::default(), Default::default());
+		assert!(genesis.has_plausible_roots());
+	}
+
+	#[test]
+	fn header_reports_its_hashing() {
+		assert!(TestHeader::uses_hashing(BlakeTwo256::HASHING_ID));
+		assert!(!TestHeader::uses_hashing(Keccak256::HASHING_ID));
+		assert!(Header::<u64, Keccak256, Vec<u8>>::uses_hashing(Keccak256::HASHING_ID));
+	}
+
+	#[test]
+	fn non_genesis_header_with_cleared_state_root_is_not_plausible() {
+		let header: TestHeader = HeaderT::new(1, [1u8; 32].into(), Default::default(), Default::default(), Default::default());
+		assert!(!header.has_plausible_roots());
+	}
+
+	#[test]
+	fn identical_blocks_have_identical_canonical_encoding() {
+		let make_block = || block_with_logs(7, vec![TestLog(1, 7)]);
+		let mut a = make_block();
+		let mut b = make_block();
+		a.extrinsics = vec![3, 1, 2];
+		b.extrinsics = vec![3, 1, 2];
+
+		assert_eq!(a.canonical_encode(), b.canonical_encode());
+		assert_eq!(a.canonical_encode(), a.encode());
+
+		b.extrinsics = vec![1, 2, 3];
+		assert!(a.canonical_encode() != b.canonical_encode());
+	}
+
+	#[test]
+	fn subset_root_preserves_extrinsic_indices() {
+		let mut block = block_with_logs(1, vec![]);
+		block.extrinsics = (0..200u64).collect();
+		block.header.extrinsics_root = BlakeTwo256::ordered_trie_root(block.extrinsics.iter().map(Slicable::encode));
+
+		assert_eq!(block.subset_root(|_| true), block.header.extrinsics_root);
+		assert_eq!(block.subset_root(|_| false), BlakeTwo256::ordered_trie_root(Vec::<Vec<u8>>::new()));
+
+		// odd extrinsics keep their odd indices, so the root differs from the one of the compacted list.
+		let odd = block.subset_root(|xt| xt % 2 == 1);
+		assert!(odd != block.header.extrinsics_root);
+		assert!(odd != BlakeTwo256::ordered_trie_root(block.extrinsics.iter().filter(|xt| *xt % 2 == 1).map(Slicable::encode)));
+		assert_eq!(odd, block.subset_root(|xt| xt % 2 == 1));
+	}
+
+	#[test]
+	fn header_only_block_has_full_block_hash() {
+		let mut block = block_with_logs(3, vec![TestLog(1, 3)]);
+		block.extrinsics = vec![1, 2, 3];
+		block.header.extrinsics_root = BlakeTwo256::ordered_trie_root(block.extrinsics.iter().map(Slicable::encode));
+		assert!(!block.is_header_only());
+
+		let header_only: super::Block<_, u64> = BlockT::header_only(block.header.clone());
+		assert!(header_only.is_header_only());
+		assert_eq!(header_only.hash(), block.hash());
+		let empty: super::Block<_, u64> = BlockT::new(block.header.clone(), vec![]);
+		assert_eq!(header_only.hash(), empty.hash());
+	}
 }
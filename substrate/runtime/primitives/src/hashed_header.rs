@@ -0,0 +1,197 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Header wrapper remembering its hash.
+
+use std::fmt;
+use std::sync::Mutex;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use codec::{Slicable, Input};
+use traits::{self, Hashing};
+
+/// Header which computes its hash once, on first use, rather than on every call of `hash()`.
+/// Any setter forgets the hash, so it is computed again for the modified header.
+///
+/// Encodes, serializes and compares exactly like the wrapped header.
+pub struct HashedHeader<H: traits::Header> {
+	inner: H,
+	hash: Mutex<Option<H::Hash>>,
+}
+
+impl<H: traits::Header> HashedHeader<H> {
+	/// The wrapped header.
+	pub fn inner(&self) -> &H {
+		&self.inner
+	}
+
+	/// Unwrap the header.
+	pub fn into_inner(self) -> H {
+		self.inner
+	}
+
+	fn forget_hash(&mut self) {
+		*self.hash.get_mut().expect("hash lock poisoned") = None;
+	}
+}
+
+impl<H: traits::Header> From<H> for HashedHeader<H> {
+	fn from(inner: H) -> Self {
+		HashedHeader { inner, hash: Mutex::new(None) }
+	}
+}
+
+impl<H: traits::Header> Clone for HashedHeader<H> {
+	fn clone(&self) -> Self {
+		HashedHeader {
+			inner: self.inner.clone(),
+			hash: Mutex::new(*self.hash.lock().expect("hash lock poisoned")),
+		}
+	}
+}
+
+impl<H: traits::Header> PartialEq for HashedHeader<H> {
+	fn eq(&self, other: &Self) -> bool {
+		self.inner == other.inner
+	}
+}
+
+impl<H: traits::Header> Eq for HashedHeader<H> {}
+
+impl<H: traits::Header> fmt::Debug for HashedHeader<H> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::Debug::fmt(&self.inner, f)
+	}
+}
+
+impl<H: traits::Header> Serialize for HashedHeader<H> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.inner.serialize(serializer)
+	}
+}
+
+impl<'a, H: traits::Header> Deserialize<'a> for HashedHeader<H> {
+	fn deserialize<D: Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+		H::deserialize(deserializer).map(Into::into)
+	}
+}
+
+impl<H: traits::Header> Slicable for HashedHeader<H> {
+	fn decode<I: Input>(input: &mut I) -> Option<Self> {
+		H::decode(input).map(Into::into)
+	}
+
+	fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
+		self.inner.using_encoded(f)
+	}
+}
+
+impl<H: traits::Header> traits::Header for HashedHeader<H> {
+	type Number = H::Number;
+	type Hash = H::Hash;
+	type Hashing = H::Hashing;
+	type Digest = H::Digest;
+
+	fn new(
+		number: Self::Number,
+		extrinsics_root: Self::Hash,
+		state_root: Self::Hash,
+		parent_hash: Self::Hash,
+		digest: Self::Digest
+	) -> Self {
+		H::new(number, extrinsics_root, state_root, parent_hash, digest).into()
+	}
+
+	fn number(&self) -> &Self::Number { self.inner.number() }
+	fn set_number(&mut self, number: Self::Number) {
+		self.forget_hash();
+		self.inner.set_number(number)
+	}
+
+	fn extrinsics_root(&self) -> &Self::Hash { self.inner.extrinsics_root() }
+	fn set_extrinsics_root(&mut self, root: Self::Hash) {
+		self.forget_hash();
+		self.inner.set_extrinsics_root(root)
+	}
+
+	fn state_root(&self) -> &Self::Hash { self.inner.state_root() }
+	fn set_state_root(&mut self, root: Self::Hash) {
+		self.forget_hash();
+		self.inner.set_state_root(root)
+	}
+
+	fn parent_hash(&self) -> &Self::Hash { self.inner.parent_hash() }
+	fn set_parent_hash(&mut self, hash: Self::Hash) {
+		self.forget_hash();
+		self.inner.set_parent_hash(hash)
+	}
+
+	fn digest(&self) -> &Self::Digest { self.inner.digest() }
+	fn set_digest(&mut self, digest: Self::Digest) {
+		self.forget_hash();
+		self.inner.set_digest(digest)
+	}
+
+	fn hash(&self) -> Self::Hash {
+		*self.hash.lock().expect("hash lock poisoned")
+			.get_or_insert_with(|| <Self::Hashing as Hashing>::hash_of(&self.inner))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use codec::Slicable;
+	use testing::{Digest, Header};
+	use traits::Header as HeaderT;
+	use super::HashedHeader;
+
+	fn header() -> HashedHeader<Header> {
+		HeaderT::new(1, Default::default(), [1u8; 32].into(), [2u8; 32].into(), Digest { logs: vec![3] })
+	}
+
+	#[test]
+	fn hash_is_computed_once() {
+		let header = header();
+		assert!(header.hash.lock().unwrap().is_none());
+
+		let hash = header.hash();
+		assert_eq!(hash, header.inner().hash());
+		assert_eq!(*header.hash.lock().unwrap(), Some(hash));
+		assert_eq!(header.hash(), hash);
+	}
+
+	#[test]
+	fn setter_forgets_hash() {
+		let mut header = header();
+		let original = header.hash();
+
+		header.set_number(2);
+		assert!(header.hash.lock().unwrap().is_none());
+		assert!(header.hash() != original);
+		assert_eq!(header.hash(), header.inner().hash());
+
+		header.set_digest(Digest { logs: vec![4] });
+		assert_eq!(header.hash(), header.clone().into_inner().hash());
+	}
+
+	#[test]
+	fn encodes_like_inner_header() {
+		let header = header();
+		assert_eq!(header.encode(), header.inner().encode());
+		assert_eq!(HashedHeader::<Header>::decode(&mut &header.encode()[..]), Some(header.clone()));
+		::testing::assert_header_roundtrip(header);
+		::testing::assert_header_roundtrip(Header::new(1, Default::default(), [1u8; 32].into(), [2u8; 32].into(), Digest { logs: vec![3] }));
+	}
+}
@@ -36,6 +36,9 @@ extern crate substrate_primitives;
 
 #[cfg(test)]
 extern crate serde_json;
+#[cfg(test)]
+#[macro_use]
+extern crate hex_literal;
 
 #[cfg(feature = "std")]
 use std::collections::HashMap;
@@ -45,10 +48,14 @@ use substrate_primitives::hash::{H256, H512};
 
 #[cfg(feature = "std")]
 pub mod testing;
+#[cfg(feature = "std")]
+pub mod hashed_header;
 
 pub mod traits;
 pub mod generic;
 pub mod bft;
+pub mod arithmetic;
+pub mod balance;
 
 use traits::{Verify, Lazy};
 
@@ -80,6 +87,87 @@ impl BuildStorage for StorageMap {
 	}
 }
 
+/// Class of a dispatchable call, used to prioritise calls and to apply block limits.
+#[derive(Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub enum DispatchClass {
+	/// An ordinary call.
+	Normal,
+	/// A call made to keep the chain operational.
+	Operational,
+	/// A call that must be included in every block.
+	Mandatory,
+}
+
+/// Error of a runtime trait operation. Converts to and from the `&'static str` errors the traits
+/// have been using, so that they can move over one at a time.
+#[derive(Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum RuntimeError {
+	/// The signature does not match the signed data.
+	BadSignature,
+	/// The account index is not known.
+	BadIndex,
+	/// The account can't afford the payment.
+	InsufficientFunds,
+	/// Any other error.
+	Other(&'static str),
+}
+
+impl From<RuntimeError> for &'static str {
+	fn from(e: RuntimeError) -> &'static str {
+		match e {
+			RuntimeError::BadSignature => "bad signature in extrinsic",
+			RuntimeError::BadIndex => "invalid account index",
+			RuntimeError::InsufficientFunds => "insufficient funds",
+			RuntimeError::Other(message) => message,
+		}
+	}
+}
+
+impl From<&'static str> for RuntimeError {
+	fn from(message: &'static str) -> RuntimeError {
+		match message {
+			"bad signature in extrinsic" => RuntimeError::BadSignature,
+			"invalid account index" => RuntimeError::BadIndex,
+			"insufficient funds" => RuntimeError::InsufficientFunds,
+			message => RuntimeError::Other(message),
+		}
+	}
+}
+
+/// Permill is parts-per-million (i.e. after multiplying by this, divide by 1000000).
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Default)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct Permill(u32);
+
+impl Permill {
+	/// Wraps the argument into `Permill` type.
+	pub fn from_millionths(x: u32) -> Permill { Permill(x) }
+
+	/// Converts percents into `Permill`, clamping to 100.
+	pub fn from_percent(x: u32) -> Permill { Permill(rstd::cmp::min(x, 100) * 10_000) }
+
+	/// Converts a fraction into `Permill`.
+	#[cfg(feature = "std")]
+	pub fn from_fraction(x: f64) -> Permill { Permill((x * 1_000_000.0) as u32) }
+
+	/// Number of millionths this `Permill` represents.
+	pub fn deconstruct(self) -> u32 { self.0 }
+}
+
+impl<N> ::rstd::ops::Mul<N> for Permill where N: traits::As<u128> + traits::Bounded {
+	type Output = N;
+	fn mul(self, b: N) -> Self::Output {
+		traits::PerThing::mul_of(self, b)
+	}
+}
+
+impl codec::Slicable for Permill {
+	fn decode<I: codec::Input>(input: &mut I) -> Option<Self> { Some(Permill(codec::Slicable::decode(input)?)) }
+	fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R { self.0.using_encoded(f) }
+}
+
 /// Ed25519 signature verify.
 #[derive(Eq, PartialEq, Clone, Default)]
 #[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
@@ -90,6 +178,13 @@ impl Verify for Ed25519Signature {
 	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &Self::Signer) -> bool {
 		runtime_io::ed25519_verify(&(self.0).0, msg.get(), &signer.0[..])
 	}
+
+	fn verify_batch<L: Lazy<[u8]>>(items: &mut [(Self, L, &Self::Signer)]) -> bool {
+		let batch: Vec<_> = items.iter_mut()
+			.map(|&mut (ref signature, ref mut msg, signer)| (&(signature.0).0, msg.get(), &signer.0[..]))
+			.collect();
+		runtime_io::ed25519_verify_batch(&batch)
+	}
 }
 
 impl codec::Slicable for Ed25519Signature {
@@ -201,28 +296,120 @@ impl<T> From<T> for MaybeUnsigned<T> {
 	}
 }
 
-/// Verify a signature on an encoded value in a lazy manner. This can be
-/// an optimization if the signature scheme has an "unsigned" escape hash.
-pub fn verify_encoded_lazy<V: Verify, T: codec::Slicable>(sig: &V, item: &T, signer: &V::Signer) -> bool {
-	// The `Lazy<T>` trait expresses something like `X: FnMut<Output = for<'a> &'a T>`.
-	// unfortunately this is a lifetime relationship that can't
-	// be expressed without generic associated types, better unification of HRTBs in type position,
-	// and some kind of integration into the Fn* traits.
-	struct LazyEncode<F> {
-		inner: F,
-		encoded: Option<Vec<u8>>,
+/// Signature verification against a set of signers, succeeding if any one of them signed.
+#[derive(Eq, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct AnyOf<T>(pub T);
+
+impl<T: Verify> Verify for AnyOf<T> {
+	type Signer = Vec<T::Signer>;
+	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signers: &Self::Signer) -> bool {
+		signers.iter().any(|signer| self.0.verify(&mut msg, signer))
 	}
+}
 
-	impl<F: Fn() -> Vec<u8>> traits::Lazy<[u8]> for LazyEncode<F> {
-		fn get(&mut self) -> &[u8] {
-			self.encoded.get_or_insert_with(&self.inner).as_slice()
+impl<T: codec::Slicable> codec::Slicable for AnyOf<T> {
+	fn decode<I: codec::Input>(input: &mut I) -> Option<Self> { Some(AnyOf(codec::Slicable::decode(input)?)) }
+	fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R { self.0.using_encoded(f) }
+}
+
+impl<T> From<T> for AnyOf<T> {
+	fn from(t: T) -> Self {
+		AnyOf(t)
+	}
+}
+
+/// Signature, or signer, of either of two signature schemes.
+#[derive(Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub enum EitherScheme<A, B> {
+	/// Of the first scheme.
+	First(A),
+	/// Of the second scheme.
+	Second(B),
+}
+
+/// Members of a multi-signature group, each of either scheme, of which at least `threshold`
+/// distinct members have to sign.
+#[derive(Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct ThresholdSigners<A, B> {
+	/// Number of distinct members required.
+	pub threshold: u32,
+	/// Group members.
+	pub members: Vec<EitherScheme<A, B>>,
+}
+
+/// Multi-signature by members of a group mixing two signature schemes: signatures paired with the
+/// index of the signing member. Every signature is checked by the scheme of its member, and a
+/// member only counts once however many signatures it contributes or times it's listed.
+#[derive(Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct HeterogeneousMultiSignature<A, B>(pub Vec<(u32, EitherScheme<A, B>)>);
+
+impl<A: Verify, B: Verify> Verify for HeterogeneousMultiSignature<A, B> where
+	A::Signer: PartialEq,
+	B::Signer: PartialEq,
+{
+	type Signer = ThresholdSigners<A::Signer, B::Signer>;
+	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signers: &Self::Signer) -> bool {
+		let mut approvals: Vec<&EitherScheme<A::Signer, B::Signer>> = Vec::new();
+		for &(index, ref signature) in &self.0 {
+			if approvals.len() >= signers.threshold as usize {
+				break;
+			}
+
+			let member = match signers.members.get(index as usize) {
+				Some(member) => member,
+				None => continue,
+			};
+			if approvals.contains(&member) {
+				continue;
+			}
+
+			let valid = match (signature, member) {
+				(&EitherScheme::First(ref signature), &EitherScheme::First(ref signer)) => signature.verify(&mut msg, signer),
+				(&EitherScheme::Second(ref signature), &EitherScheme::Second(ref signer)) => signature.verify(&mut msg, signer),
+				_ => false,
+			};
+			if valid {
+				approvals.push(member);
+			}
 		}
+
+		approvals.len() >= signers.threshold as usize
+	}
+}
+
+/// The message signed for `item`: its encoding, produced on the first `get` and only if a
+/// signature scheme actually asks for it.
+pub struct SignatureFor<'a, T: 'a> {
+	item: &'a T,
+	encoded: Option<Vec<u8>>,
+}
+
+impl<'a, T: codec::Slicable> SignatureFor<'a, T> {
+	/// Lazily encoded message for `item`.
+	pub fn new(item: &'a T) -> Self {
+		SignatureFor { item, encoded: None }
 	}
+}
 
-	sig.verify(
-		LazyEncode { inner: || item.encode(), encoded: None },
-		signer,
-	)
+// The `Lazy<T>` trait expresses something like `X: FnMut<Output = for<'a> &'a T>`.
+// unfortunately this is a lifetime relationship that can't
+// be expressed without generic associated types, better unification of HRTBs in type position,
+// and some kind of integration into the Fn* traits.
+impl<'a, T: codec::Slicable> Lazy<[u8]> for SignatureFor<'a, T> {
+	fn get(&mut self) -> &[u8] {
+		let item = self.item;
+		self.encoded.get_or_insert_with(|| item.encode()).as_slice()
+	}
+}
+
+/// Verify a signature on an encoded value in a lazy manner. This can be
+/// an optimization if the signature scheme has an "unsigned" escape hash.
+pub fn verify_encoded_lazy<V: Verify, T: codec::Slicable>(sig: &V, item: &T, signer: &V::Signer) -> bool {
+	sig.verify(SignatureFor::new(item), signer)
 }
 
 #[macro_export]
@@ -260,3 +447,229 @@ macro_rules! impl_outer_config {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::cell::Cell;
+	use codec::Slicable;
+	use substrate_primitives::hash::{H256, H512};
+	use traits::{Verify, Lazy};
+	use super::{AnyOf, Ed25519Signature, EitherScheme, HeterogeneousMultiSignature, Permill, RuntimeError, SignatureFor,
+		ThresholdSigners, verify_encoded_lazy};
+
+	/// Signature which is valid for the signer it names, counting verification attempts.
+	struct NamedSignature {
+		signer: u64,
+		attempts: Cell<usize>,
+	}
+
+	impl NamedSignature {
+		fn new(signer: u64) -> Self {
+			NamedSignature { signer, attempts: Cell::new(0) }
+		}
+	}
+
+	impl Verify for NamedSignature {
+		type Signer = u64;
+		fn verify<L: Lazy<[u8]>>(&self, _msg: L, signer: &u64) -> bool {
+			self.attempts.set(self.attempts.get() + 1);
+			*signer == self.signer
+		}
+	}
+
+	/// Signature which is valid only over the message it carries.
+	struct MessageSignature(Vec<u8>);
+
+	impl Verify for MessageSignature {
+		type Signer = ();
+		fn verify<L: Lazy<[u8]>>(&self, mut msg: L, _signer: &()) -> bool {
+			msg.get() == &self.0[..]
+		}
+	}
+
+	#[test]
+	fn any_of_verifies_against_any_listed_signer() {
+		let signature = AnyOf(NamedSignature::new(2));
+		assert!(verify_encoded_lazy(&signature, &42u32, &vec![1, 2, 3]));
+		assert!(!verify_encoded_lazy(&signature, &42u32, &vec![1, 3]));
+	}
+
+	#[test]
+	fn any_of_stops_at_first_matching_signer() {
+		let signature = AnyOf(NamedSignature::new(1));
+		assert!(verify_encoded_lazy(&signature, &42u32, &vec![1, 2, 3]));
+		assert_eq!(signature.0.attempts.get(), 1);
+	}
+
+	#[test]
+	fn any_of_rejects_empty_signer_list() {
+		let signature = AnyOf(NamedSignature::new(1));
+		assert!(!verify_encoded_lazy(&signature, &42u32, &vec![]));
+		assert_eq!(signature.0.attempts.get(), 0);
+	}
+
+	#[test]
+	fn any_of_passes_message_to_every_attempt() {
+		let signature = AnyOf(MessageSignature(42u32.encode()));
+		assert!(verify_encoded_lazy(&signature, &42u32, &vec![(), ()]));
+		assert!(!verify_encoded_lazy(&signature, &43u32, &vec![(), ()]));
+	}
+
+	/// Signature of a second scheme, valid for the signer it names.
+	struct ShortSignature(u8);
+
+	impl Verify for ShortSignature {
+		type Signer = u8;
+		fn verify<L: Lazy<[u8]>>(&self, _msg: L, signer: &u8) -> bool {
+			*signer == self.0
+		}
+	}
+
+	fn group(threshold: u32) -> ThresholdSigners<u64, u8> {
+		ThresholdSigners {
+			threshold,
+			members: vec![EitherScheme::First(1), EitherScheme::Second(2), EitherScheme::First(3), EitherScheme::First(1)],
+		}
+	}
+
+	fn signed_by(members: &[(u32, EitherScheme<u64, u8>)]) -> HeterogeneousMultiSignature<NamedSignature, ShortSignature> {
+		HeterogeneousMultiSignature(members.iter().map(|&(index, ref signer)| (index, match *signer {
+			EitherScheme::First(signer) => EitherScheme::First(NamedSignature::new(signer)),
+			EitherScheme::Second(signer) => EitherScheme::Second(ShortSignature(signer)),
+		})).collect())
+	}
+
+	#[test]
+	fn mixed_scheme_multi_signature_passes_threshold() {
+		let signature = signed_by(&[(0, EitherScheme::First(1)), (1, EitherScheme::Second(2))]);
+		assert!(verify_encoded_lazy(&signature, &42u32, &group(2)));
+		assert!(!verify_encoded_lazy(&signature, &42u32, &group(3)));
+	}
+
+	#[test]
+	fn multi_signature_below_threshold_is_rejected() {
+		// wrong signer for member 2 and a signature of the wrong scheme for member 1.
+		let signature = signed_by(&[(0, EitherScheme::First(1)), (2, EitherScheme::First(4)), (1, EitherScheme::First(2))]);
+		assert!(verify_encoded_lazy(&signature, &42u32, &group(1)));
+		assert!(!verify_encoded_lazy(&signature, &42u32, &group(2)));
+	}
+
+	#[test]
+	fn duplicate_members_count_once() {
+		// member 0 signing twice, and again as member 3 which has the same key.
+		let signature = signed_by(&[(0, EitherScheme::First(1)), (0, EitherScheme::First(1)), (3, EitherScheme::First(1))]);
+		assert!(!verify_encoded_lazy(&signature, &42u32, &group(2)));
+	}
+
+	/// Message counting how many times it has been asked for.
+	struct CountingLazy {
+		msg: Vec<u8>,
+		gets: usize,
+	}
+
+	impl Lazy<[u8]> for CountingLazy {
+		fn get(&mut self) -> &[u8] {
+			self.gets += 1;
+			&self.msg
+		}
+	}
+
+	// RFC 8032, test 2.
+	fn ed25519_vector() -> (Ed25519Signature, H256) {
+		let signature = hex!("92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00");
+		let public = hex!("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c");
+		(Ed25519Signature(H512::from(signature)), H256::from(public))
+	}
+
+	#[test]
+	fn ed25519_gets_lazy_message_once() {
+		let (signature, signer) = ed25519_vector();
+		let mut msg = CountingLazy { msg: vec![0x72], gets: 0 };
+		assert!(signature.verify(&mut msg, &signer));
+		assert_eq!(msg.gets, 1);
+
+		let mut msg = CountingLazy { msg: vec![0x73], gets: 0 };
+		assert!(!signature.verify(&mut msg, &signer));
+		assert_eq!(msg.gets, 1);
+	}
+
+	#[test]
+	fn ed25519_batch_fails_on_any_bad_signature() {
+		let (signature, signer) = ed25519_vector();
+		let msg = |byte: u8| CountingLazy { msg: vec![byte], gets: 0 };
+
+		let mut batch = vec![(signature.clone(), msg(0x72), &signer), (signature.clone(), msg(0x72), &signer)];
+		assert!(Ed25519Signature::verify_batch(&mut batch));
+
+		batch.insert(1, (signature.clone(), msg(0x73), &signer));
+		assert!(!Ed25519Signature::verify_batch(&mut batch));
+	}
+
+	#[test]
+	fn default_batch_fails_on_any_bad_signature() {
+		let msg = |byte: u8| CountingLazy { msg: vec![byte], gets: 0 };
+		let signature = |byte: u8| MessageSignature(vec![byte]);
+
+		assert!(MessageSignature::verify_batch(&mut [(signature(1), msg(1), &()), (signature(2), msg(2), &())]));
+		assert!(!MessageSignature::verify_batch(&mut [(signature(1), msg(1), &()), (signature(2), msg(3), &()), (signature(4), msg(4), &())]));
+		assert!(MessageSignature::verify_batch::<CountingLazy>(&mut []));
+	}
+
+	/// Value counting how many times it has been encoded.
+	struct CountingEncode {
+		value: u32,
+		encodes: Cell<usize>,
+	}
+
+	impl Slicable for CountingEncode {
+		fn decode<I: ::codec::Input>(input: &mut I) -> Option<Self> {
+			u32::decode(input).map(|value| CountingEncode { value, encodes: Cell::new(0) })
+		}
+		fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
+			self.encodes.set(self.encodes.get() + 1);
+			self.value.using_encoded(f)
+		}
+	}
+
+	#[test]
+	fn signature_for_encodes_only_when_asked() {
+		let item = CountingEncode { value: 42, encodes: Cell::new(0) };
+		assert!(!AnyOf(NamedSignature::new(1)).verify(SignatureFor::new(&item), &vec![2, 3]));
+		assert_eq!(item.encodes.get(), 0);
+
+		// every attempt fails, but the item is encoded for the first one only.
+		let signature = AnyOf(MessageSignature(43u32.encode()));
+		assert!(!signature.verify(SignatureFor::new(&item), &vec![(), (), ()]));
+		assert_eq!(item.encodes.get(), 1);
+	}
+
+	#[test]
+	fn runtime_error_messages() {
+		let message = |e: RuntimeError| -> &'static str { e.into() };
+		assert_eq!(message(RuntimeError::BadSignature), "bad signature in extrinsic");
+		assert_eq!(message(RuntimeError::BadIndex), "invalid account index");
+		assert_eq!(message(RuntimeError::InsufficientFunds), "insufficient funds");
+		assert_eq!(message(RuntimeError::Other("unknown call")), "unknown call");
+	}
+
+	#[test]
+	fn runtime_error_round_trips_through_message() {
+		for &e in &[RuntimeError::BadSignature, RuntimeError::BadIndex, RuntimeError::InsufficientFunds, RuntimeError::Other("unknown call")] {
+			let message: &'static str = e.into();
+			assert_eq!(RuntimeError::from(message), e);
+		}
+	}
+
+	#[test]
+	fn permill_multiplies_wide_values() {
+		assert_eq!(Permill::from_percent(50) * (1u128 << 100), 1u128 << 99);
+		assert_eq!(Permill::from_percent(100) * u128::max_value(), u128::max_value());
+		assert_eq!(Permill::from_millionths(1) * 999_999u64, 0);
+	}
+
+	#[test]
+	fn permill_from_percent_clamps_to_hundred() {
+		assert_eq!(Permill::from_percent(150), Permill::from_percent(100));
+		assert_eq!(Permill::from_percent(u32::max_value()).deconstruct(), 1_000_000);
+	}
+}
@@ -41,6 +41,9 @@ impl traits::Digest for Digest {
 	fn push(&mut self, item: Self::Item) {
 		self.logs.push(item);
 	}
+	fn logs(&self) -> &[Self::Item] {
+		&self.logs
+	}
 }
 
 #[derive(PartialEq, Eq, Clone, Serialize, Deserialize, Debug)]
@@ -108,6 +111,23 @@ impl traits::Header for Header {
 	}
 }
 
+/// Assert that `header` decodes from its own encoding, with every field read back through the
+/// getters of `traits::Header` as it was given, so that the codec of the header can't drift from its
+/// fields.
+pub fn assert_header_roundtrip<H: traits::Header>(header: H) {
+	use traits::Header as HeaderT;
+
+	let encoded = header.encode();
+	let decoded = H::decode(&mut &encoded[..]).expect("header decodes from its own encoding");
+	assert_eq!(decoded.number(), header.number(), "number does not round-trip");
+	assert_eq!(decoded.extrinsics_root(), header.extrinsics_root(), "extrinsics root does not round-trip");
+	assert_eq!(decoded.state_root(), header.state_root(), "state root does not round-trip");
+	assert_eq!(decoded.parent_hash(), header.parent_hash(), "parent hash does not round-trip");
+	assert_eq!(decoded.digest(), header.digest(), "digest does not round-trip");
+	assert_eq!(decoded.hash(), header.hash(), "hash does not round-trip");
+	assert_eq!(decoded.encode(), encoded);
+}
+
 #[derive(PartialEq, Eq, Clone, Serialize, Deserialize, Debug)]
 pub struct Block<Xt: Slicable + Sized + Send + Sync + Serialize + Clone + Eq + Debug> {
 	pub header: Header,
@@ -24,7 +24,7 @@ use runtime_io;
 use substrate_primitives;
 use codec::Slicable;
 pub use integer_sqrt::IntegerSquareRoot;
-pub use num_traits::{Zero, One, Bounded};
+pub use num_traits::{Zero, One, Bounded, CheckedAdd, CheckedSub, CheckedMul, CheckedDiv};
 use rstd::ops::{Add, Sub, Mul, Div, Rem, AddAssign, SubAssign, MulAssign, DivAssign, RemAssign};
 
 /// A lazy value.
@@ -32,12 +32,24 @@ pub trait Lazy<T: ?Sized> {
 	fn get(&mut self) -> &T;
 }
 
+impl<'a, T: ?Sized, L: Lazy<T>> Lazy<T> for &'a mut L {
+	fn get(&mut self) -> &T {
+		(**self).get()
+	}
+}
+
 /// Means of signature verification.
 pub trait Verify {
 	/// Type of the signer.
 	type Signer;
 	/// Verify a signature.
 	fn verify<L: Lazy<[u8]>>(&self, msg: L, signer: &Self::Signer) -> bool;
+
+	/// Verify a batch of signatures, each with its message and signer. `false` if any of them is
+	/// invalid. Schemes which can verify a batch faster than one signature at a time override this.
+	fn verify_batch<L: Lazy<[u8]>>(items: &mut [(Self, L, &Self::Signer)]) -> bool where Self: Sized {
+		items.iter_mut().all(|&mut (ref signature, ref mut msg, signer)| signature.verify(msg, signer))
+	}
 }
 
 /// Means of changing one type into another in a manner dependent on the source type.
@@ -50,6 +62,38 @@ pub trait AuxLookup {
 	fn lookup(s: Self::Source) -> result::Result<Self::Target, &'static str>;
 }
 
+/// Lookup through `A` and then through `B`, failing with the first error.
+pub struct Chained<A, B>(rstd::marker::PhantomData<(A, B)>);
+impl<A: AuxLookup, B: AuxLookup<Source = A::Target>> AuxLookup for Chained<A, B> {
+	type Source = A::Source;
+	type Target = B::Target;
+	fn lookup(s: Self::Source) -> result::Result<Self::Target, &'static str> {
+		B::lookup(A::lookup(s)?)
+	}
+}
+
+/// Lookup through `L`, falling back to the source itself, unchanged, when `L` has no entry for it.
+pub struct OrIdentity<L>(rstd::marker::PhantomData<L>);
+impl<L: AuxLookup> AuxLookup for OrIdentity<L> where L::Source: Clone + Into<L::Target> {
+	type Source = L::Source;
+	type Target = L::Target;
+	fn lookup(s: Self::Source) -> result::Result<Self::Target, &'static str> {
+		Ok(L::lookup(s.clone()).unwrap_or_else(|_| s.into()))
+	}
+}
+
+/// Lookup of the account at a compact `u32` index, as referenced by extrinsics in place of the full
+/// account id. The account is read through `Accounts`, typically a getter of an index storage map;
+/// an index without an account fails. Pass `IndexLookup::lookup` as the lookup of `Checkable::check`.
+pub struct IndexLookup<Accounts, AccountId>(rstd::marker::PhantomData<(Accounts, AccountId)>);
+impl<Accounts: Convert<u32, Option<AccountId>>, AccountId> AuxLookup for IndexLookup<Accounts, AccountId> {
+	type Source = u32;
+	type Target = AccountId;
+	fn lookup(index: u32) -> result::Result<AccountId, &'static str> {
+		Accounts::convert(index).ok_or("unknown account index")
+	}
+}
+
 /// Simple payment making trait, operating on a single generic `AccountId` type.
 pub trait MakePayment<AccountId> {
 	/// Make some sort of payment concerning `who` for an extrinsic (transaction) of encoded length
@@ -61,6 +105,32 @@ impl<T> MakePayment<T> for () {
 	fn make_payment(_: &T, _: usize) -> Result<(), &'static str> { Ok(()) }
 }
 
+/// Free balances of accounts, which payments can be deducted from.
+pub trait Currency<AccountId> {
+	/// The balance type.
+	type Balance;
+	/// The free balance of `who`.
+	fn free_balance(who: &AccountId) -> Self::Balance;
+	/// Set the free balance of `who`.
+	fn set_free_balance(who: &AccountId, balance: Self::Balance);
+}
+
+/// Payment deducting a fee of `C::convert(encoded_len)` from the free balance in `Cur`.
+pub struct WeightedPayment<Cur, C>(rstd::marker::PhantomData<(Cur, C)>);
+
+impl<AccountId, Cur, C> MakePayment<AccountId> for WeightedPayment<Cur, C> where
+	Cur: Currency<AccountId>,
+	Cur::Balance: SimpleArithmetic,
+	C: Convert<usize, Cur::Balance>,
+{
+	fn make_payment(who: &AccountId, encoded_len: usize) -> Result<(), &'static str> {
+		let fee = C::convert(encoded_len);
+		let remaining = Cur::free_balance(who).checked_sub(&fee).ok_or(::RuntimeError::InsufficientFunds)?;
+		Cur::set_free_balance(who, remaining);
+		Ok(())
+	}
+}
+
 /// Extensible conversion trait. Generic over both source and destination types.
 pub trait Convert<A, B> {
 	/// Make conversion.
@@ -76,6 +146,66 @@ pub trait As<T> {
 	fn sa(T) -> Self;
 }
 
+/// Numeric conversion which clamps to the bounds of the target type rather than truncating.
+pub trait SaturatedFrom<T> {
+	/// Convert `t`, saturating at the minimum or maximum value of `Self`.
+	fn saturated_from(t: T) -> Self;
+}
+
+/// Convenience for converting a numeric into any type it can be saturated into.
+pub trait SaturatedAs: Sized {
+	/// Convert into `T`, saturating at its minimum or maximum value (ala `As::as_`).
+	fn saturated_as<T: SaturatedFrom<Self>>(self) -> T {
+		T::saturated_from(self)
+	}
+}
+
+impl<T> SaturatedAs for T {}
+
+/// Convert `a` into `B` through `u128`, clamping to the bounds of `B` rather than truncating. Also
+/// works for non-primitive numerics, unlike `SaturatedAs`. Signed types are recognised by their
+/// negative minimum, which is sign-extended above their maximum when widened.
+pub fn clamp_into<A: As<u128> + Bounded, B: As<u128> + Bounded>(a: A) -> B {
+	let a_is_negative = is_signed::<A>() && a.as_() > A::max_value().as_();
+	let (a, min, max) = (a.as_(), B::min_value().as_(), B::max_value().as_());
+	if a_is_negative {
+		// both negative values are sign-extended, so they compare as `i128`.
+		match is_signed::<B>() && a as i128 >= min as i128 {
+			true => B::sa(a),
+			false => B::min_value(),
+		}
+	} else if a > max {
+		B::max_value()
+	} else if !is_signed::<B>() && a < min {
+		B::min_value()
+	} else {
+		B::sa(a)
+	}
+}
+
+/// True if the minimum of `N` is negative, i.e. sign-extended above its maximum when widened.
+fn is_signed<N: As<u128> + Bounded>() -> bool {
+	N::min_value().as_() > N::max_value().as_()
+}
+
+/// Whether `n` is negative, and its magnitude.
+fn sign_and_magnitude<N: As<u128> + Bounded>(n: N) -> (bool, u128) {
+	match is_signed::<N>() && n.as_() > N::max_value().as_() {
+		// sign-extended, so negating as `i128` gives the magnitude, even for `i128::min_value()`.
+		true => (true, (n.as_() as i128).wrapping_neg() as u128),
+		false => (false, n.as_()),
+	}
+}
+
+/// The value of given sign and magnitude, clamped to the bounds of `N`.
+fn from_sign_and_magnitude<N: As<u128> + Bounded>(negative: bool, magnitude: u128) -> N {
+	match negative {
+		true if magnitude > i128::max_value() as u128 + 1 => N::min_value(),
+		true => clamp_into((magnitude as i128).wrapping_neg()),
+		false => clamp_into(magnitude),
+	}
+}
+
 macro_rules! impl_numerics {
 	( $( $t:ty ),* ) => {
 		$(
@@ -87,6 +217,16 @@ macro_rules! impl_numerics {
 			fn as_(self) -> $t { self as $t }
 			fn sa(t: $t) -> Self { t as Self }
 		}
+		impl SaturatedFrom<$f> for $t {
+			#[allow(unused_comparisons)]
+			fn saturated_from(f: $f) -> Self {
+				if f < 0 {
+					if (f as i128) < (<$t>::min_value() as i128) { <$t>::min_value() } else { f as $t }
+				} else {
+					if (f as u128) > (<$t>::max_value() as u128) { <$t>::max_value() } else { f as $t }
+				}
+			}
+		}
 		impl_numerics!($f: $( $rest, )*);
 	};
 	( $f:ty : ) => {}
@@ -99,6 +239,76 @@ impl<T> Convert<T, T> for Identity {
 	fn convert(a: T) -> T { a }
 }
 
+/// A constant value provided by a type.
+pub trait Get<T> {
+	/// Return the value.
+	fn get() -> T;
+}
+
+/// Scales a value by the ratio `N / D`, rounding down and saturating at the maximum value. Values
+/// are widened to `u128`, so `value * N` may exceed the value type. A zero `D` leaves the value
+/// unchanged.
+pub struct RationalConvert<N, D>(rstd::marker::PhantomData<(N, D)>);
+impl<T: As<u128> + Bounded, N: Get<u64>, D: Get<u64>> Convert<T, T> for RationalConvert<N, D> {
+	fn convert(value: T) -> T {
+		let (numerator, denominator) = (N::get() as u128, D::get() as u128);
+		if denominator == 0 {
+			return value;
+		}
+
+		// the remainder is below `denominator`, so its product with `numerator` fits in `u128`.
+		let value = value.as_();
+		let scaled = (value / denominator).saturating_mul(numerator)
+			.saturating_add(value % denominator * numerator / denominator);
+		if scaled >= T::max_value().as_() { T::max_value() } else { T::sa(scaled) }
+	}
+}
+
+/// Scales a value by the ratio `N / D` like `RationalConvert`, but rounding to the nearest value,
+/// halves up, so that repeated scaling isn't biased downwards. A zero `D` leaves the value
+/// unchanged.
+pub struct RoundedRationalConvert<N, D>(rstd::marker::PhantomData<(N, D)>);
+impl<T: As<u128> + Bounded, N: Get<u64>, D: Get<u64>> Convert<T, T> for RoundedRationalConvert<N, D> {
+	fn convert(value: T) -> T {
+		let (numerator, denominator) = (N::get() as u128, D::get() as u128);
+		if denominator == 0 {
+			return value;
+		}
+
+		// as in `RationalConvert`, with half the denominator added to the fractional part.
+		let value = value.as_();
+		let scaled = (value / denominator).saturating_mul(numerator)
+			.saturating_add((value % denominator * numerator + denominator / 2) / denominator);
+		if scaled >= T::max_value().as_() { T::max_value() } else { T::sa(scaled) }
+	}
+}
+
+/// Maps a relay chain block number to the parachain slot it falls in, with slots of `D` relay
+/// blocks each starting at block zero. A zero `D` maps every block to slot zero.
+pub struct RelayToParaSlot<D>(rstd::marker::PhantomData<D>);
+impl<N: As<u64>, D: Get<u64>> Convert<N, u64> for RelayToParaSlot<D> {
+	fn convert(block: N) -> u64 {
+		block.as_().checked_div(D::get()).unwrap_or(0)
+	}
+}
+
+/// A static table of call dispatch classes, keyed by `(module index, call index)`.
+pub trait DispatchClassTable {
+	/// Calls with a class other than `DispatchClass::Normal`.
+	fn classes() -> &'static [((u8, u8), ::DispatchClass)];
+}
+
+/// Maps a call index to its dispatch class using the table `T`. Calls not in the table are normal.
+pub struct WeightToClass<T>(rstd::marker::PhantomData<T>);
+impl<T: DispatchClassTable> Convert<(u8, u8), ::DispatchClass> for WeightToClass<T> {
+	fn convert(index: (u8, u8)) -> ::DispatchClass {
+		T::classes().iter()
+			.find(|&&(i, _)| i == index)
+			.map(|&(_, class)| class)
+			.unwrap_or(::DispatchClass::Normal)
+	}
+}
+
 pub trait MaybeEmpty {
 	fn is_empty(&self) -> bool;
 }
@@ -127,8 +337,48 @@ pub trait SimpleArithmetic:
 	Mul<Self, Output = Self> + MulAssign<Self> +
 	Div<Self, Output = Self> + DivAssign<Self> +
 	Rem<Self, Output = Self> + RemAssign<Self> +
+	CheckedAdd + CheckedSub + CheckedMul + CheckedDiv +
 	PartialOrd<Self> + Ord
-{}
+{
+	/// Apply the checked operation `op` to `a` and `b`, reporting the operation and its operands
+	/// when it overflows, underflows or divides by zero.
+	fn checked_op(a: Self, op: ::arithmetic::ArithOp, b: Self) -> Result<Self, ::arithmetic::ArithError<Self>> {
+		use arithmetic::ArithOp;
+
+		let result = match op {
+			ArithOp::Add => a.checked_add(&b),
+			ArithOp::Sub => a.checked_sub(&b),
+			ArithOp::Mul => a.checked_mul(&b),
+			ArithOp::Div => a.checked_div(&b),
+		};
+		result.ok_or_else(|| ::arithmetic::ArithError { op, lhs: a, rhs: b })
+	}
+
+	/// Divide by `other`, rounding to the nearest integer and halves to the even one (bankers'
+	/// rounding) rather than truncating. Meant for non-negative values; panics if `other` is zero.
+	fn div_round_even(self, other: Self) -> Self where Self: Copy {
+		let two = Self::one() + Self::one();
+		let (quotient, remainder) = (self / other, self % other);
+		let rest = other - remainder;
+		if remainder > rest || (remainder == rest && quotient % two == Self::one()) {
+			quotient + Self::one()
+		} else {
+			quotient
+		}
+	}
+
+	/// Add `add` to `self`, stopping at `cap`, and return the amount actually added. A total which
+	/// is already at or above `cap` is left untouched.
+	fn accumulate_capped(&mut self, add: Self, cap: Self) -> Self where Self: Copy {
+		if *self >= cap {
+			return Self::zero();
+		}
+
+		let added = if add > cap - *self { cap - *self } else { add };
+		*self += added;
+		added
+	}
+}
 impl<T:
 	Zero + One + IntegerSquareRoot + As<usize> +
 	Add<Self, Output = Self> + AddAssign<Self> +
@@ -136,9 +386,149 @@ impl<T:
 	Mul<Self, Output = Self> + MulAssign<Self> +
 	Div<Self, Output = Self> + DivAssign<Self> +
 	Rem<Self, Output = Self> + RemAssign<Self> +
+	CheckedAdd + CheckedSub + CheckedMul + CheckedDiv +
 	PartialOrd<Self> + Ord
 > SimpleArithmetic for T {}
 
+/// Addition and subtraction clamping to the bounds of the type rather than overflowing, for
+/// accounting which can't go below zero or above the maximum. Never panics. Meant for non-negative
+/// values; named after the inherent methods of the primitive types, which take precedence on them.
+pub trait Saturating: SimpleArithmetic + Bounded {
+	/// `self + other`, clamped to `max_value()`.
+	fn saturating_add(self, other: Self) -> Self {
+		self.checked_add(&other).unwrap_or_else(Self::max_value)
+	}
+
+	/// `self - other`, clamped to zero.
+	fn saturating_sub(self, other: Self) -> Self {
+		self.checked_sub(&other).unwrap_or_else(Self::zero)
+	}
+}
+impl<T: SimpleArithmetic + Bounded> Saturating for T {}
+
+/// A percentage, clamped to at most 100%.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Default)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct Percent(u8);
+
+impl Percent {
+	/// Converts percents into `Percent`, clamping to 100.
+	pub fn from_percent(x: u8) -> Percent { Percent(rstd::cmp::min(x, 100)) }
+
+	/// Number of percents this `Percent` represents.
+	pub fn deconstruct(self) -> u8 { self.0 }
+
+	/// This percentage of `n`, rounded down. Same as `PerThing::mul_of`.
+	pub fn mul<N: SimpleArithmetic + As<u128> + Bounded>(self, n: N) -> N {
+		self.mul_of(n)
+	}
+}
+
+impl Add for Percent {
+	type Output = Percent;
+	fn add(self, other: Percent) -> Percent { Percent::from_percent(self.0.saturating_add(other.0)) }
+}
+
+impl Mul for Percent {
+	type Output = Percent;
+	fn mul(self, other: Percent) -> Percent { Percent((self.0 as u16 * other.0 as u16 / 100) as u8) }
+}
+
+impl Zero for Percent {
+	fn zero() -> Percent { Percent(0) }
+	fn is_zero(&self) -> bool { self.0 == 0 }
+}
+
+impl One for Percent {
+	fn one() -> Percent { Percent(100) }
+}
+
+/// A fraction of the whole, in parts of a fixed accuracy. Integers can be multiplied and divided
+/// by it directly, e.g. `amount * Percent::from_percent(20)`.
+pub trait PerThing: Sized + Copy {
+	/// Number of parts making up the whole.
+	fn accuracy() -> u32;
+
+	/// The fraction of `parts` parts, clamped to the whole.
+	fn from_parts(parts: u32) -> Self;
+
+	/// Number of parts this fraction represents.
+	fn parts(self) -> u32;
+
+	/// This fraction of `n`, rounded towards zero.
+	fn mul_of<N: As<u128> + Bounded>(self, n: N) -> N {
+		let (negative, n) = sign_and_magnitude(n);
+		let (parts, accuracy) = (self.parts() as u128, Self::accuracy() as u128);
+		from_sign_and_magnitude(negative, n / accuracy * parts + n % accuracy * parts / accuracy)
+	}
+
+	/// The value this fraction of which is `n`, rounded towards zero and saturating at the maximum
+	/// value, or the minimum for a negative `n`. Dividing by a zero fraction saturates.
+	fn div_of<N: As<u128> + Bounded>(self, n: N) -> N {
+		let (negative, n) = sign_and_magnitude(n);
+		let (parts, accuracy) = (self.parts() as u128, Self::accuracy() as u128);
+		from_sign_and_magnitude(negative, n.checked_mul(accuracy).and_then(|n| n.checked_div(parts)).unwrap_or(u128::max_value()))
+	}
+}
+
+impl PerThing for Percent {
+	fn accuracy() -> u32 { 100 }
+	fn from_parts(parts: u32) -> Percent { Percent(rstd::cmp::min(parts, 100) as u8) }
+	fn parts(self) -> u32 { self.0 as u32 }
+}
+
+impl PerThing for ::Permill {
+	fn accuracy() -> u32 { 1_000_000 }
+	fn from_parts(parts: u32) -> ::Permill { ::Permill::from_millionths(rstd::cmp::min(parts, 1_000_000)) }
+	fn parts(self) -> u32 { self.deconstruct() }
+}
+
+macro_rules! impl_per_thing_ops {
+	( $( $per:ty ),* ; $( $n:ty ),* ) => {
+		$( impl_per_thing_ops!(@per $per ; $( $n ),*); )*
+	};
+	( @per $per:ty ; $( $n:ty ),* ) => {
+		$(
+			impl Mul<$per> for $n {
+				type Output = $n;
+				fn mul(self, fraction: $per) -> $n { fraction.mul_of(self) }
+			}
+			impl Div<$per> for $n {
+				type Output = $n;
+				fn div(self, fraction: $per) -> $n { fraction.div_of(self) }
+			}
+		)*
+	};
+}
+
+impl_per_thing_ops!(Percent, ::Permill; u8, u16, u32, u64, u128, usize);
+
+/// Fee multiplier adapting to block fullness: scales a base fee by `1 + K * (fullness - Ideal)`,
+/// with `Ideal` and `K` in percent. Fees fall below the base fee for blocks emptier than `Ideal`,
+/// never below zero, and rise above it for fuller blocks, saturating at the maximum value. A negative
+/// base fee is no fee at all.
+pub struct TargetedFeeAdjustment<Ideal, K>(rstd::marker::PhantomData<(Ideal, K)>);
+impl<Ideal: Get<u64>, K: Get<u64>> TargetedFeeAdjustment<Ideal, K> {
+	/// The fee for a block of given `fullness`, with `base` the fee at ideal fullness.
+	pub fn adjust<N: As<u128> + Bounded + Zero + PartialOrd>(base: N, fullness: Percent) -> N {
+		// the multiplier, in ten-thousandths.
+		let offset = fullness.deconstruct() as i128 - Ideal::get() as i128;
+		let multiplier = 10_000 + K::get() as i128 * offset;
+		if multiplier <= 0 || base < N::zero() {
+			return clamp_into(0u128);
+		}
+
+		let (base, multiplier) = (base.as_(), multiplier as u128);
+		clamp_into((base / 10_000).saturating_mul(multiplier)
+			.saturating_add(base % 10_000 * multiplier / 10_000))
+	}
+}
+impl<N: As<u128> + Bounded + Zero + PartialOrd, Ideal: Get<u64>, K: Get<u64>> Convert<(N, Percent), N> for TargetedFeeAdjustment<Ideal, K> {
+	fn convert((base, fullness): (N, Percent)) -> N {
+		Self::adjust(base, fullness)
+	}
+}
+
 /// Trait for things that can be clear (have no bits set). For numeric types, essentially the same
 /// as `Zero`.
 pub trait Clear {
@@ -180,20 +570,63 @@ impl<A: Executable, B: Executable> Executable for (A, B) {
 	}
 }
 
+/// Something that can be executed and may fail.
+pub trait TryExecutable {
+	fn try_execute() -> Result<(), &'static str>;
+}
+
+impl TryExecutable for () {
+	fn try_execute() -> Result<(), &'static str> { Ok(()) }
+}
+impl<A: TryExecutable, B: TryExecutable> TryExecutable for (A, B) {
+	fn try_execute() -> Result<(), &'static str> {
+		A::try_execute()?;
+		B::try_execute()
+	}
+}
+
+/// Adapts an `Executable` into a `TryExecutable` which always succeeds. This is a wrapper rather
+/// than a blanket impl, since that would overlap with the tuple impl above.
+pub struct Infallible<T>(rstd::marker::PhantomData<T>);
+impl<T: Executable> TryExecutable for Infallible<T> {
+	fn try_execute() -> Result<(), &'static str> {
+		T::execute();
+		Ok(())
+	}
+}
+
 /// Abstraction around hashing
 pub trait Hashing: 'static + MaybeSerializeDebug + Clone + Eq + PartialEq {	// Stupid bug in the Rust compiler believes derived
 																	// traits must be fulfilled by all type parameters.
 	/// The hash type produced.
 	type Output: Member + AsRef<[u8]>;
 
+	/// Identifier of the hashing algorithm and trie layout, unique among `Hashing` implementations.
+	const HASHING_ID: u16;
+
 	/// Produce the hash of some byte-slice.
 	fn hash(s: &[u8]) -> Self::Output;
 
+	/// Produce the hash of some byte-slice, failing rather than trapping when the hashing backend is
+	/// not available, e.g. in a restricted runtime without the host function.
+	fn try_hash(s: &[u8]) -> result::Result<Self::Output, &'static str> {
+		Ok(Self::hash(s))
+	}
+
 	/// Produce the hash of some codec-encodable value.
 	fn hash_of<S: Slicable>(s: &S) -> Self::Output {
 		Slicable::using_encoded(s, Self::hash)
 	}
 
+	/// Produce the hash of the concatenation of `slices`.
+	fn hash_slices(slices: &[&[u8]]) -> Self::Output {
+		let mut buffer = Vec::new();
+		for slice in slices {
+			buffer.extend_from_slice(slice);
+		}
+		Self::hash(&buffer)
+	}
+
 	/// Produce the patricia-trie root of a mapping from indices to byte slices.
 	fn enumerated_trie_root(items: &[&[u8]]) -> Self::Output;
 
@@ -203,6 +636,18 @@ pub trait Hashing: 'static + MaybeSerializeDebug + Clone + Eq + PartialEq {	// S
 		A: AsRef<[u8]>
 	>(input: I) -> Self::Output;
 
+	/// Order-independent commitment to a set: the `ordered_trie_root` of the sorted, deduplicated
+	/// `items`.
+	fn sorted_set_root<
+		I: IntoIterator<Item = A>,
+		A: AsRef<[u8]> + Ord
+	>(items: I) -> Self::Output {
+		let mut items: Vec<A> = items.into_iter().collect();
+		items.sort();
+		items.dedup();
+		Self::ordered_trie_root(items)
+	}
+
 	/// The Patricia tree root of the given mapping as an iterator.
 	fn trie_root<
 		I: IntoIterator<Item = (A, B)>,
@@ -212,6 +657,17 @@ pub trait Hashing: 'static + MaybeSerializeDebug + Clone + Eq + PartialEq {	// S
 
 	/// Acquire the global storage root.
 	fn storage_root() -> Self::Output;
+
+	/// Produce the storage key of `item` of `module`, for map entries followed by the encoded
+	/// `params`. Storage keys are always hashed with `twox_128`, whatever the hashing.
+	fn storage_key(module: &[u8], item: &[u8], params: &[&[u8]]) -> Vec<u8> {
+		let mut key = module.to_vec();
+		key.extend_from_slice(item);
+		for param in params {
+			key.extend_from_slice(param);
+		}
+		runtime_io::twox_128(&key).to_vec()
+	}
 }
 
 /// Blake2-256 Hashing implementation.
@@ -221,9 +677,45 @@ pub struct BlakeTwo256;
 
 impl Hashing for BlakeTwo256 {
 	type Output = substrate_primitives::H256;
+	const HASHING_ID: u16 = 1;
 	fn hash(s: &[u8]) -> Self::Output {
 		runtime_io::blake2_256(s).into()
 	}
+	fn hash_slices(slices: &[&[u8]]) -> Self::Output {
+		runtime_io::blake2_256_slices(slices).into()
+	}
+	fn enumerated_trie_root(items: &[&[u8]]) -> Self::Output {
+		runtime_io::enumerated_trie_root(items).into()
+	}
+	fn trie_root<
+		I: IntoIterator<Item = (A, B)>,
+		A: AsRef<[u8]> + Ord,
+		B: AsRef<[u8]>
+	>(input: I) -> Self::Output {
+		runtime_io::trie_root(input).into()
+	}
+	fn ordered_trie_root<
+		I: IntoIterator<Item = A>,
+		A: AsRef<[u8]>
+	>(input: I) -> Self::Output {
+		runtime_io::ordered_trie_root(input).into()
+	}
+	fn storage_root() -> Self::Output {
+		runtime_io::storage_root().into()
+	}
+}
+
+/// Keccak-256 Hashing implementation.
+#[derive(PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct Keccak256;
+
+impl Hashing for Keccak256 {
+	type Output = substrate_primitives::H256;
+	const HASHING_ID: u16 = 2;
+	fn hash(s: &[u8]) -> Self::Output {
+		runtime_io::keccak_256(s).into()
+	}
 	fn enumerated_trie_root(items: &[&[u8]]) -> Self::Output {
 		runtime_io::enumerated_trie_root(items).into()
 	}
@@ -245,30 +737,123 @@ impl Hashing for BlakeTwo256 {
 	}
 }
 
+/// Blake2-256 Hashing implementation over the secure trie layout, in which every trie key is
+/// hashed before insertion. For checking the extrinsics and other trie roots of chains using that
+/// layout natively; not for use as a runtime's hashing.
+///
+/// Only available with `std`, as the wasm runtime can't build secure-layout tries. The state
+/// backend only supports the standard layout, so `storage_root` is the standard-layout root of the
+/// externalities, not a secure-layout one.
+#[cfg(feature = "std")]
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct BlakeTwo256Legacy;
+
+#[cfg(feature = "std")]
+impl Hashing for BlakeTwo256Legacy {
+	type Output = substrate_primitives::H256;
+	const HASHING_ID: u16 = 3;
+	fn hash(s: &[u8]) -> Self::Output {
+		runtime_io::blake2_256(s).into()
+	}
+	fn hash_slices(slices: &[&[u8]]) -> Self::Output {
+		runtime_io::blake2_256_slices(slices).into()
+	}
+	fn enumerated_trie_root(items: &[&[u8]]) -> Self::Output {
+		Self::ordered_trie_root(items.iter())
+	}
+	fn trie_root<
+		I: IntoIterator<Item = (A, B)>,
+		A: AsRef<[u8]> + Ord,
+		B: AsRef<[u8]>
+	>(input: I) -> Self::Output {
+		runtime_io::sec_trie_root(input).into()
+	}
+	fn ordered_trie_root<
+		I: IntoIterator<Item = A>,
+		A: AsRef<[u8]>
+	>(input: I) -> Self::Output {
+		runtime_io::sec_trie_root(input.into_iter().enumerate().map(|(i, v)| (ordered_trie_key(i), v))).into()
+	}
+	fn storage_root() -> Self::Output {
+		runtime_io::storage_root().into()
+	}
+}
+
 /// Something that can be checked for equality and printed out to a debug channel if bad.
 pub trait CheckEqual {
-	fn check_equal(&self, other: &Self);
+	/// Check for equality, only printing the mismatch if not equal.
+	fn check_equal(&self, other: &Self) {
+		let _ = self.assert_equal(other);
+	}
+
+	/// Check for equality like `check_equal`, also failing if not equal so that the caller can
+	/// react to the mismatch.
+	fn assert_equal(&self, other: &Self) -> Result<(), &'static str>;
 }
 
 impl CheckEqual for substrate_primitives::H256 {
 	#[cfg(feature = "std")]
-	fn check_equal(&self, other: &Self) {
+	fn assert_equal(&self, other: &Self) -> Result<(), &'static str> {
 		use substrate_primitives::hexdisplay::HexDisplay;
 		if &self.0 != &other.0 {
 			println!("Hash: given={}, expected={}", HexDisplay::from(&self.0), HexDisplay::from(&other.0));
+			return Err("not equal");
 		}
+		Ok(())
 	}
 
 	#[cfg(not(feature = "std"))]
-	fn check_equal(&self, other: &Self) {
+	fn assert_equal(&self, other: &Self) -> Result<(), &'static str> {
 		if self != other {
 			runtime_io::print("Hash not equal");
 			runtime_io::print(&self.0[..]);
 			runtime_io::print(&other.0[..]);
+			return Err("not equal");
 		}
+		Ok(())
+	}
+}
+
+#[cfg(feature = "std")]
+fn check_equal_debug<T: PartialEq + Debug>(given: &T, expected: &T) -> Result<(), &'static str> {
+	if given != expected {
+		println!("CheckEqual failed: given={:?}, expected={:?}", given, expected);
+		return Err("not equal");
+	}
+	Ok(())
+}
+
+#[cfg(not(feature = "std"))]
+fn check_equal_debug<T: PartialEq>(given: &T, expected: &T) -> Result<(), &'static str> {
+	if given != expected {
+		runtime_io::print("CheckEqual failed");
+		return Err("not equal");
+	}
+	Ok(())
+}
+
+macro_rules! impl_check_equal_debug {
+	( $( $t:ty ),* ) => {
+		$(
+			impl CheckEqual for $t {
+				fn assert_equal(&self, other: &Self) -> Result<(), &'static str> { check_equal_debug(self, other) }
+			}
+		)*
 	}
 }
 
+impl_check_equal_debug!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+#[cfg(feature = "std")]
+impl<Item: PartialEq + Debug> CheckEqual for ::generic::Digest<Item> {
+	fn assert_equal(&self, other: &Self) -> Result<(), &'static str> { check_equal_debug(self, other) }
+}
+
+#[cfg(not(feature = "std"))]
+impl<Item: PartialEq> CheckEqual for ::generic::Digest<Item> {
+	fn assert_equal(&self, other: &Self) -> Result<(), &'static str> { check_equal_debug(self, other) }
+}
+
 #[cfg(feature = "std")]
 pub trait MaybeSerializeDebugButNotDeserialize: Serialize + Debug {}
 #[cfg(feature = "std")]
@@ -289,6 +874,73 @@ pub trait MaybeSerializeDebug {}
 #[cfg(not(feature = "std"))]
 impl<T> MaybeSerializeDebug for T {}
 
+/// Human-readable output without allocation, for use in the runtime (e.g. via `runtime_io::print`)
+/// where `Display` is not available.
+pub trait RuntimeDisplay {
+	/// Write the representation into `buf`, truncated to its length, returning the number of bytes
+	/// written.
+	fn fmt_to(&self, buf: &mut [u8]) -> usize;
+}
+
+fn write_decimal(mut value: u128, negative: bool, buf: &mut [u8]) -> usize {
+	let mut digits = [0u8; 40];
+	let mut len = 0;
+	loop {
+		digits[len] = b'0' + (value % 10) as u8;
+		len += 1;
+		value /= 10;
+		if value == 0 {
+			break;
+		}
+	}
+	if negative {
+		digits[len] = b'-';
+		len += 1;
+	}
+
+	let written = rstd::cmp::min(len, buf.len());
+	for i in 0..written {
+		buf[i] = digits[len - 1 - i];
+	}
+	written
+}
+
+macro_rules! impl_runtime_display {
+	(unsigned: $( $t:ty ),*; signed: $( $s:ty ),*) => {
+		$(
+			impl RuntimeDisplay for $t {
+				fn fmt_to(&self, buf: &mut [u8]) -> usize {
+					write_decimal(*self as u128, false, buf)
+				}
+			}
+		)*
+		$(
+			impl RuntimeDisplay for $s {
+				fn fmt_to(&self, buf: &mut [u8]) -> usize {
+					let value = *self as i128;
+					// the magnitude of `i128::min_value()` only fits once reinterpreted as unsigned.
+					let magnitude = if value < 0 { value.wrapping_neg() as u128 } else { value as u128 };
+					write_decimal(magnitude, value < 0, buf)
+				}
+			}
+		)*
+	}
+}
+
+impl_runtime_display!(unsigned: u8, u16, u32, u64, u128, usize; signed: i8, i16, i32, i64, i128, isize);
+
+impl RuntimeDisplay for substrate_primitives::H256 {
+	fn fmt_to(&self, buf: &mut [u8]) -> usize {
+		const HEX: &[u8; 16] = b"0123456789abcdef";
+		let written = rstd::cmp::min(self.0.len() * 2, buf.len());
+		for i in 0..written {
+			let byte = self.0[i / 2];
+			buf[i] = HEX[if i % 2 == 0 { byte >> 4 } else { byte & 0xf } as usize];
+		}
+		written
+	}
+}
+
 #[cfg(feature = "std")]
 pub trait MaybeDisplay: Display {}
 #[cfg(feature = "std")]
@@ -307,6 +959,26 @@ impl<T: Send + Sync + Sized + MaybeSerializeDebug + Eq + PartialEq + Clone + 'st
 pub trait Digest {
 	type Item: Member;
 	fn push(&mut self, item: Self::Item);
+
+	/// All logs, in the order they were pushed.
+	fn logs(&self) -> &[Self::Item];
+
+	/// Find the first log for which `f` returns something.
+	fn find<T, F: Fn(&Self::Item) -> Option<&T>>(&self, f: F) -> Option<&T> {
+		self.logs().iter().filter_map(f).next()
+	}
+}
+
+/// Digest log item which may redundantly record the number of the block it belongs to.
+pub trait NumberLog<Number> {
+	/// The recorded block number, if this is a block-number log.
+	fn as_number(&self) -> Option<Number>;
+}
+
+/// Something that acts like a `Digest` and may carry a block-number log.
+pub trait NumberDigest<Number> {
+	/// The block number recorded in the logs, if any.
+	fn number_log(&self) -> Option<Number>;
 }
 
 /// Something which fulfills the abstract idea of a Substrate header. It has types for a `Number`,
@@ -346,6 +1018,16 @@ pub trait Header: Clone + Send + Sync + Slicable + Eq + MaybeSerializeDebug {
 	fn hash(&self) -> Self::Hash {
 		<Self::Hashing as Hashing>::hash_of(self)
 	}
+
+	/// Whether headers of this type are hashed with the algorithm identified by `id`.
+	fn uses_hashing(id: u16) -> bool {
+		<Self::Hashing as Hashing>::HASHING_ID == id
+	}
+
+	/// Cheap sanity check: any block past genesis must have non-clear state and extrinsics roots.
+	fn has_plausible_roots(&self) -> bool {
+		self.number().is_zero() || (!self.state_root().is_clear() && !self.extrinsics_root().is_clear())
+	}
 }
 
 /// Something which fulfills the abstract idea of a Substrate block. It has types for an
@@ -364,6 +1046,70 @@ pub trait Block: Clone + Send + Sync + Slicable + Eq + MaybeSerializeDebug {
 	fn hash(&self) -> Self::Hash {
 		<<Self::Header as Header>::Hashing as Hashing>::hash_of(self.header())
 	}
+
+	/// Block made of just `header`, for nodes which have pruned or never fetched the body. Its hash
+	/// is the one of the full block.
+	fn header_only(header: Self::Header) -> Self {
+		Self::new(header, Vec::new())
+	}
+
+	/// Whether the block carries no extrinsics, as a block built with `header_only` does.
+	fn is_header_only(&self) -> bool {
+		self.extrinsics().is_empty()
+	}
+
+	/// Trie root of the extrinsics matching `filter`, each keyed by its index in the block. With a
+	/// filter matching every extrinsic this is the `extrinsics_root`.
+	fn subset_root<F: Fn(&Self::Extrinsic) -> bool>(&self, filter: F) -> Self::Hash {
+		<<Self::Header as Header>::Hashing as Hashing>::trie_root(self.extrinsics().iter()
+			.enumerate()
+			.filter(|&(_, extrinsic)| filter(extrinsic))
+			.map(|(index, extrinsic)| (ordered_trie_key(index), extrinsic.encode())))
+	}
+
+	/// Canonical encoding of the block: the encoded header (the preimage of `hash()`) followed
+	/// by the length-prefixed extrinsics in the order they were included.
+	fn canonical_encode(&self) -> Vec<u8> {
+		let mut encoded = self.header().encode();
+		debug_assert!(
+			<<Self::Header as Header>::Hashing as Hashing>::hash(&encoded) == self.hash(),
+			"header encoding must be the preimage of the block hash"
+		);
+
+		let extrinsics = self.extrinsics();
+		assert!(extrinsics.len() <= u32::max_value() as usize, "Attempted to encode block with too many extrinsics.");
+		(extrinsics.len() as u32).using_encoded(|len| encoded.extend_from_slice(len));
+		for extrinsic in extrinsics {
+			extrinsic.using_encoded(|e| encoded.extend_from_slice(e));
+		}
+		encoded
+	}
+
+	/// Check that the block-number log of the digest, if present, matches the header number.
+	fn check_number_log(&self) -> bool where
+		<Self::Header as Header>::Digest: NumberDigest<<Self::Header as Header>::Number>
+	{
+		let header = self.header();
+		header.digest().number_log().map_or(true, |number| &number == header.number())
+	}
+}
+
+/// Key of the item at `index` in an `ordered_trie_root`: the index, RLP-encoded.
+pub fn ordered_trie_key(index: usize) -> Vec<u8> {
+	if index == 0 {
+		return vec![0x80];
+	}
+	if index < 0x80 {
+		return vec![index as u8];
+	}
+
+	let significant: Vec<u8> = (0..8).rev()
+		.map(|byte| ((index as u64) >> (byte * 8)) as u8)
+		.skip_while(|&b| b == 0)
+		.collect();
+	let mut key = vec![0x80 + significant.len() as u8];
+	key.extend(significant);
+	key
 }
 
 /// Extract the hashing type for a block.
@@ -377,6 +1123,15 @@ pub trait Checkable: Sized + Send + Sync {
 	type Checked: Member;
 	fn sender(&self) -> &Self::Address;
 	fn check<ThisLookup: FnOnce(Self::Address) -> Result<Self::AccountId, &'static str> + Send + Sync>(self, lookup: ThisLookup) -> Result<Self::Checked, &'static str>;
+
+	/// Check, also returning the weight of the checked information. By default this is the
+	/// encoded length, which is what `MakePayment` charges for.
+	fn check_with_weight<ThisLookup: FnOnce(Self::Address) -> Result<Self::AccountId, &'static str> + Send + Sync>(self, lookup: ThisLookup) -> Result<(Self::Checked, usize), &'static str> where
+		Self: Slicable
+	{
+		let weight = self.using_encoded(|encoded| encoded.len());
+		self.check(lookup).map(|checked| (checked, weight))
+	}
 }
 
 /// A "checkable" piece of information, used by the standard Substrate Executive in order to
@@ -410,4 +1165,673 @@ pub trait Applyable: Sized + Send + Sync {
 	fn index(&self) -> &Self::Index;
 	fn sender(&self) -> &Self::AccountId;
 	fn apply(self) -> Result<(), &'static str>;
+
+	/// Apply, reporting failure as a `RuntimeError`.
+	fn try_apply(self) -> Result<(), ::RuntimeError> {
+		self.apply().map_err(Into::into)
+	}
+}
+
+/// Apply `items` in order, stopping at the first failure and reporting its index and error.
+///
+/// Items before the failing one stay applied: the caller owns the transactional boundary and is
+/// expected to revert storage when an error is returned.
+pub fn apply_all<A: Applyable, I: IntoIterator<Item = A>>(items: I) -> Result<(), (usize, &'static str)> {
+	for (index, item) in items.into_iter().enumerate() {
+		item.apply().map_err(|e| (index, e))?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use codec::Slicable;
+	use substrate_primitives::H256;
+	use DispatchClass;
+	use std::cell::RefCell;
+	use std::collections::HashMap;
+	use super::{apply_all, Applyable, AuxLookup, IndexLookup, OrIdentity, Currency, MakePayment, WeightedPayment, BlakeTwo256, BlakeTwo256Legacy, One, Percent, PerThing, Saturating, Zero, BlindCheckable, Chained, Checkable, CheckEqual, Convert, Get, RationalConvert, RoundedRationalConvert, RelayToParaSlot, TargetedFeeAdjustment, DispatchClassTable, Executable, RuntimeDisplay, Hashing, Infallible, Keccak256,
+		SaturatedAs, SimpleArithmetic, TryExecutable, WeightToClass, As, Bounded, clamp_into};
+
+	fn checked<N: SimpleArithmetic>(a: N, b: N) -> (Option<N>, Option<N>, Option<N>, Option<N>) {
+		(a.checked_add(&b), a.checked_sub(&b), a.checked_mul(&b), a.checked_div(&b))
+	}
+
+	#[test]
+	fn checked_arithmetic_detects_u64_overflow() {
+		assert_eq!(checked(u64::max_value(), 1), (None, Some(u64::max_value() - 1), Some(u64::max_value()), Some(u64::max_value())));
+		assert_eq!(checked(0u64, 1).1, None);
+		assert_eq!(checked(1u64 << 32, 1 << 32).2, None);
+		assert_eq!(checked(1u64, 0).3, None);
+	}
+
+	#[test]
+	fn checked_arithmetic_detects_u128_overflow() {
+		let max = u128::max_value();
+		assert_eq!(checked(max, 1), (None, Some(max - 1), Some(max), Some(max)));
+		assert_eq!(checked(max - 1, 1).0, Some(max));
+		assert_eq!(checked(1u128 << 64, 1 << 64).2, None);
+		assert_eq!(checked((1u128 << 64) - 1, 1 << 64).2, Some(max - ((1 << 64) - 1)));
+	}
+
+	struct TestTable;
+	impl DispatchClassTable for TestTable {
+		fn classes() -> &'static [((u8, u8), DispatchClass)] {
+			&[((0, 1), DispatchClass::Operational), ((3, 0), DispatchClass::Mandatory)]
+		}
+	}
+
+	#[test]
+	fn known_call_is_mapped_to_its_class() {
+		assert_eq!(WeightToClass::<TestTable>::convert((0, 1)), DispatchClass::Operational);
+		assert_eq!(WeightToClass::<TestTable>::convert((3, 0)), DispatchClass::Mandatory);
+	}
+
+	#[test]
+	fn unknown_call_is_normal() {
+		assert_eq!(WeightToClass::<TestTable>::convert((0, 2)), DispatchClass::Normal);
+	}
+
+	#[test]
+	fn keccak_hash_of_matches_hash_of_encoding() {
+		assert_eq!(Keccak256::hash_of(&42u64), Keccak256::hash(&42u64.encode()));
+		assert_eq!(Keccak256::hash_of(&b"hello".to_vec()), Keccak256::hash(&b"hello".to_vec().encode()));
+		assert_eq!(Keccak256::hash_of(&(1u32, 2u64)), Keccak256::hash(&(1u32, 2u64).encode()));
+	}
+
+	#[test]
+	fn keccak_matches_known_vectors() {
+		assert_eq!(Keccak256::hash(&[]), H256::from(hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")));
+		let empty_trie_root = H256::from(hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"));
+		assert_eq!(Keccak256::enumerated_trie_root(&[]), empty_trie_root);
+		assert_eq!(Keccak256::ordered_trie_root(Vec::<Vec<u8>>::new()), empty_trie_root);
+		assert_eq!(Keccak256::trie_root(Vec::<(Vec<u8>, Vec<u8>)>::new()), empty_trie_root);
+	}
+
+	#[test]
+	fn saturated_as_clamps_out_of_range_values() {
+		assert_eq!(u128::max_value().saturated_as::<u32>(), u32::max_value());
+		assert_eq!(u64::max_value().saturated_as::<i64>(), i64::max_value());
+		assert_eq!((-1i32).saturated_as::<u64>(), 0);
+		assert_eq!(i128::min_value().saturated_as::<i8>(), i8::min_value());
+	}
+
+	#[test]
+	fn saturated_as_keeps_in_range_values() {
+		assert_eq!(1_000u128.saturated_as::<u32>(), 1_000);
+		assert_eq!((-5i64).saturated_as::<i8>(), -5);
+		assert_eq!(u32::max_value().saturated_as::<u128>(), u32::max_value() as u128);
+	}
+
+	/// Gas meter reading, of at least one unit.
+	#[derive(Debug, PartialEq)]
+	struct Gas(u32);
+	impl As<u128> for Gas {
+		fn as_(self) -> u128 { self.0 as u128 }
+		fn sa(gas: u128) -> Self { Gas(gas as u32) }
+	}
+	impl Bounded for Gas {
+		fn min_value() -> Self { Gas(1) }
+		fn max_value() -> Self { Gas(u32::max_value()) }
+	}
+
+	#[test]
+	fn clamp_into_clamps_to_bounds() {
+		assert_eq!(clamp_into::<u128, u32>(u128::max_value()), u32::max_value());
+		assert_eq!(clamp_into::<u64, Gas>(u64::max_value()), Gas(u32::max_value()));
+		assert_eq!(clamp_into::<u128, Gas>(0), Gas(1));
+	}
+
+	#[test]
+	fn clamp_into_keeps_in_range_values() {
+		assert_eq!(clamp_into::<u128, u32>(1_000), 1_000);
+		assert_eq!(clamp_into::<u8, Gas>(7), Gas(7));
+		assert_eq!(clamp_into::<u32, u64>(u32::max_value()), u32::max_value() as u64);
+	}
+
+	#[test]
+	fn clamp_into_handles_signed_types() {
+		assert_eq!(clamp_into::<u8, i32>(5), 5);
+		assert_eq!(clamp_into::<u128, i8>(1_000), i8::max_value());
+		assert_eq!(clamp_into::<u128, i128>(u128::max_value()), i128::max_value());
+		assert_eq!(clamp_into::<i32, u8>(-5), 0);
+		assert_eq!(clamp_into::<i32, u8>(300), u8::max_value());
+		assert_eq!(clamp_into::<i32, Gas>(-5), Gas(1));
+		assert_eq!(clamp_into::<i16, i64>(-7), -7);
+		assert_eq!(clamp_into::<i64, i8>(-1_000), i8::min_value());
+		assert_eq!(clamp_into::<i64, i8>(-100), -100);
+	}
+
+	thread_local! {
+		static EXECUTED: RefCell<Vec<&'static str>> = RefCell::new(Vec::new());
+	}
+
+	fn executed() -> Vec<&'static str> {
+		EXECUTED.with(|e| e.borrow_mut().drain(..).collect())
+	}
+
+	struct Migration;
+	impl Executable for Migration {
+		fn execute() { EXECUTED.with(|e| e.borrow_mut().push("migration")); }
+	}
+
+	struct FailingHook;
+	impl TryExecutable for FailingHook {
+		fn try_execute() -> Result<(), &'static str> {
+			EXECUTED.with(|e| e.borrow_mut().push("failing"));
+			Err("hook failed")
+		}
+	}
+
+	#[test]
+	fn infallible_executable_succeeds() {
+		assert_eq!(<(Infallible<Migration>, Infallible<(Migration, ())>)>::try_execute(), Ok(()));
+		assert_eq!(executed(), vec!["migration", "migration"]);
+	}
+
+	#[test]
+	fn try_execute_stops_at_first_error() {
+		assert_eq!(<(Infallible<Migration>, (FailingHook, Infallible<Migration>))>::try_execute(), Err("hook failed"));
+		assert_eq!(executed(), vec!["migration", "failing"]);
+	}
+
+	thread_local! {
+		static APPLIED: RefCell<Vec<u64>> = RefCell::new(Vec::new());
+	}
+
+	/// Extrinsic which fails to apply when its value is zero.
+	struct Nonzero(u64);
+	impl Applyable for Nonzero {
+		type AccountId = u64;
+		type Index = u64;
+		fn index(&self) -> &u64 { &self.0 }
+		fn sender(&self) -> &u64 { &self.0 }
+		fn apply(self) -> Result<(), &'static str> {
+			if self.0 == 0 { return Err("zero value"); }
+			APPLIED.with(|a| a.borrow_mut().push(self.0));
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn apply_all_applies_every_item() {
+		assert_eq!(apply_all(vec![Nonzero(1), Nonzero(2), Nonzero(3)]), Ok(()));
+		assert_eq!(APPLIED.with(|a| a.borrow_mut().drain(..).collect::<Vec<_>>()), vec![1, 2, 3]);
+	}
+
+	#[test]
+	fn apply_all_reports_first_failing_index() {
+		assert_eq!(apply_all(vec![Nonzero(1), Nonzero(2), Nonzero(0), Nonzero(4), Nonzero(0)]), Err((2, "zero value")));
+		assert_eq!(APPLIED.with(|a| a.borrow_mut().drain(..).collect::<Vec<_>>()), vec![1, 2]);
+	}
+
+	#[test]
+	fn try_apply_reports_runtime_error() {
+		assert_eq!(Nonzero(1).try_apply(), Ok(()));
+		assert_eq!(Nonzero(0).try_apply(), Err(::RuntimeError::Other("zero value")));
+		APPLIED.with(|a| a.borrow_mut().clear());
+	}
+
+	thread_local! {
+		static BALANCES: RefCell<HashMap<u64, u64>> = RefCell::new(HashMap::new());
+	}
+
+	struct MockCurrency;
+	impl Currency<u64> for MockCurrency {
+		type Balance = u64;
+		fn free_balance(who: &u64) -> u64 {
+			BALANCES.with(|b| b.borrow().get(who).cloned().unwrap_or(0))
+		}
+		fn set_free_balance(who: &u64, balance: u64) {
+			BALANCES.with(|b| b.borrow_mut().insert(*who, balance));
+		}
+	}
+
+	/// Charges 10 per byte.
+	struct PerByte;
+	impl Convert<usize, u64> for PerByte {
+		fn convert(len: usize) -> u64 { len as u64 * 10 }
+	}
+
+	type Payment = WeightedPayment<MockCurrency, PerByte>;
+
+	#[test]
+	fn weighted_payment_deducts_converted_fee() {
+		MockCurrency::set_free_balance(&1, 100);
+		assert_eq!(Payment::make_payment(&1, 3), Ok(()));
+		assert_eq!(MockCurrency::free_balance(&1), 100 - PerByte::convert(3));
+		assert_eq!(Payment::make_payment(&1, 7), Ok(()));
+		assert_eq!(MockCurrency::free_balance(&1), 0);
+	}
+
+	#[test]
+	fn weighted_payment_rejects_underfunded_account() {
+		MockCurrency::set_free_balance(&2, 29);
+		assert_eq!(Payment::make_payment(&2, 3), Err("insufficient funds"));
+		assert_eq!(MockCurrency::free_balance(&2), 29);
+		assert_eq!(Payment::make_payment(&3, 0), Ok(()));
+		assert_eq!(Payment::make_payment(&3, 1), Err("insufficient funds"));
+	}
+
+	/// Looks up the address of an account index.
+	struct IndexToAddress;
+	impl AuxLookup for IndexToAddress {
+		type Source = u32;
+		type Target = u64;
+		fn lookup(index: u32) -> Result<u64, &'static str> {
+			if index < 10 { Ok(index as u64 * 100) } else { Err("unknown index") }
+		}
+	}
+
+	/// Looks up the account of an address.
+	struct AddressToAccount;
+	impl AuxLookup for AddressToAccount {
+		type Source = u64;
+		type Target = H256;
+		fn lookup(address: u64) -> Result<H256, &'static str> {
+			if address > 0 { Ok(H256::from(address)) } else { Err("null address") }
+		}
+	}
+
+	#[test]
+	fn chained_lookup_applies_both_lookups() {
+		assert_eq!(<Chained<IndexToAddress, AddressToAccount>>::lookup(3), Ok(H256::from(300)));
+	}
+
+	#[test]
+	fn chained_lookup_propagates_first_error() {
+		assert_eq!(<Chained<IndexToAddress, AddressToAccount>>::lookup(10), Err("unknown index"));
+		assert_eq!(<Chained<IndexToAddress, AddressToAccount>>::lookup(0), Err("null address"));
+	}
+
+	/// Looks up the current address of a renamed address.
+	struct RenamedAddress;
+	impl AuxLookup for RenamedAddress {
+		type Source = u64;
+		type Target = u64;
+		fn lookup(address: u64) -> Result<u64, &'static str> {
+			if address == 1 { Ok(100) } else { Err("not renamed") }
+		}
+	}
+
+	#[test]
+	fn or_identity_lookup_prefers_table_entry() {
+		assert_eq!(<OrIdentity<RenamedAddress>>::lookup(1), Ok(100));
+		assert_eq!(<OrIdentity<IndexToAddress>>::lookup(3), Ok(300));
+	}
+
+	#[test]
+	fn or_identity_lookup_falls_back_to_source() {
+		assert_eq!(RenamedAddress::lookup(2), Err("not renamed"));
+		assert_eq!(<OrIdentity<RenamedAddress>>::lookup(2), Ok(2));
+		assert_eq!(<OrIdentity<IndexToAddress>>::lookup(10), Ok(10));
+	}
+
+	/// Accounts by index, with a gap at index 1.
+	struct IndexedAccounts;
+	impl Convert<u32, Option<H256>> for IndexedAccounts {
+		fn convert(index: u32) -> Option<H256> {
+			[Some(H256::from(10)), None, Some(H256::from(12))].get(index as usize).cloned().and_then(|a| a)
+		}
+	}
+
+	type AccountIndices = IndexLookup<IndexedAccounts, H256>;
+
+	/// Extrinsic sent from an account index, checked into the account id of its sender.
+	struct IndexSent(u32);
+	impl Checkable for IndexSent {
+		type Address = u32;
+		type AccountId = H256;
+		type Checked = H256;
+		fn sender(&self) -> &u32 { &self.0 }
+		fn check<ThisLookup: FnOnce(u32) -> Result<H256, &'static str> + Send + Sync>(self, lookup: ThisLookup) -> Result<H256, &'static str> {
+			lookup(self.0)
+		}
+	}
+
+	#[test]
+	fn index_lookup_resolves_known_indices() {
+		assert_eq!(AccountIndices::lookup(2), Ok(H256::from(12)));
+		assert_eq!(IndexSent(2).check(AccountIndices::lookup), Ok(H256::from(12)));
+	}
+
+	#[test]
+	fn index_lookup_resolves_zero_index() {
+		assert_eq!(AccountIndices::lookup(0), Ok(H256::from(10)));
+		assert_eq!(IndexSent(0).check(AccountIndices::lookup), Ok(H256::from(10)));
+	}
+
+	#[test]
+	fn index_lookup_rejects_missing_index() {
+		assert_eq!(AccountIndices::lookup(1), Err("unknown account index"));
+		assert_eq!(AccountIndices::lookup(3), Err("unknown account index"));
+		assert_eq!(IndexSent(1).check(AccountIndices::lookup), Err("unknown account index"));
+	}
+
+	#[test]
+	fn hash_slices_equals_hash_of_concatenation() {
+		let (a, b) = (&b"hello"[..], &b", world"[..]);
+		assert_eq!(BlakeTwo256::hash_slices(&[a, b]), BlakeTwo256::hash(b"hello, world"));
+		assert_eq!(BlakeTwo256::hash_slices(&[]), BlakeTwo256::hash(&[]));
+		assert_eq!(Keccak256::hash_slices(&[a, b]), Keccak256::hash(b"hello, world"));
+	}
+
+	/// Blake2-256 hashing in an environment without the hashing host function.
+	#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+	struct RestrictedBlakeTwo256;
+	impl Hashing for RestrictedBlakeTwo256 {
+		type Output = H256;
+		const HASHING_ID: u16 = 0xffff;
+		fn hash(s: &[u8]) -> H256 { BlakeTwo256::hash(s) }
+		fn try_hash(_: &[u8]) -> Result<H256, &'static str> { Err("host function unavailable") }
+		fn enumerated_trie_root(items: &[&[u8]]) -> H256 { BlakeTwo256::enumerated_trie_root(items) }
+		fn trie_root<I: IntoIterator<Item = (A, B)>, A: AsRef<[u8]> + Ord, B: AsRef<[u8]>>(input: I) -> H256 {
+			BlakeTwo256::trie_root(input)
+		}
+		fn ordered_trie_root<I: IntoIterator<Item = A>, A: AsRef<[u8]>>(input: I) -> H256 {
+			BlakeTwo256::ordered_trie_root(input)
+		}
+		fn storage_root() -> H256 { BlakeTwo256::storage_root() }
+	}
+
+	#[test]
+	fn try_hash_defaults_to_hash() {
+		assert_eq!(BlakeTwo256::try_hash(b"hello"), Ok(BlakeTwo256::hash(b"hello")));
+		assert_eq!(Keccak256::try_hash(b"hello"), Ok(Keccak256::hash(b"hello")));
+	}
+
+	#[test]
+	fn try_hash_reports_backend_failure() {
+		assert_eq!(RestrictedBlakeTwo256::try_hash(b"hello"), Err("host function unavailable"));
+	}
+
+	#[test]
+	fn check_equal_reports_mismatch_without_panicking() {
+		5u64.check_equal(&5);
+		5u64.check_equal(&6);
+		H256::from(1).check_equal(&H256::from(2));
+		::generic::Digest { logs: vec![1u64] }.check_equal(&Default::default());
+	}
+
+	#[test]
+	fn saturating_add_clamps_at_max() {
+		assert_eq!(<u32 as Saturating>::saturating_add(u32::max_value() - 1, 5), u32::max_value());
+		assert_eq!(<u32 as Saturating>::saturating_add(u32::max_value(), u32::max_value()), u32::max_value());
+		assert_eq!(<u32 as Saturating>::saturating_add(2, 3), 5);
+		assert_eq!(<u64 as Saturating>::saturating_add(u64::max_value(), 1), u64::max_value());
+		assert_eq!(<u64 as Saturating>::saturating_add(0, u64::max_value()), u64::max_value());
+	}
+
+	#[test]
+	fn saturating_sub_clamps_at_zero() {
+		assert_eq!(<u32 as Saturating>::saturating_sub(0, 5), 0);
+		assert_eq!(<u32 as Saturating>::saturating_sub(5, 5), 0);
+		assert_eq!(<u32 as Saturating>::saturating_sub(u32::max_value(), 1), u32::max_value() - 1);
+		assert_eq!(<u64 as Saturating>::saturating_sub(1, u64::max_value()), 0);
+		assert_eq!(<u64 as Saturating>::saturating_sub(u64::max_value(), 0), u64::max_value());
+	}
+
+	#[test]
+	fn assert_equal_reports_mismatch_as_error() {
+		assert_eq!(H256::from(1).assert_equal(&H256::from(1)), Ok(()));
+		assert_eq!(H256::from(1).assert_equal(&H256::from(2)), Err("not equal"));
+		assert_eq!(5u64.assert_equal(&6), Err("not equal"));
+		assert_eq!(::generic::Digest { logs: vec![1u64] }.assert_equal(&Default::default()), Err("not equal"));
+	}
+
+	#[test]
+	fn trie_layouts_do_not_alias() {
+		let items = vec![(&b"alice"[..], &b"1"[..]), (&b"bob"[..], &b"2"[..])];
+		assert!(BlakeTwo256::trie_root(items.clone()) != BlakeTwo256Legacy::trie_root(items));
+		let values = vec![&b"first"[..], b"second"];
+		assert!(BlakeTwo256::ordered_trie_root(values.clone()) != BlakeTwo256Legacy::ordered_trie_root(values.clone()));
+		assert_eq!(BlakeTwo256Legacy::enumerated_trie_root(&values), BlakeTwo256Legacy::ordered_trie_root(values));
+		assert_eq!(BlakeTwo256Legacy::hash(b"hello"), BlakeTwo256::hash(b"hello"));
+	}
+
+	#[test]
+	fn sorted_set_root_is_order_independent() {
+		let root = BlakeTwo256::sorted_set_root(vec![&b"alice"[..], b"bob", b"charlie"]);
+		assert_eq!(BlakeTwo256::sorted_set_root(vec![&b"charlie"[..], b"alice", b"bob", b"alice"]), root);
+		assert!(BlakeTwo256::sorted_set_root(vec![&b"alice"[..], b"bob", b"dave"]) != root);
+		assert!(BlakeTwo256::sorted_set_root(vec![&b"alice"[..], b"bob"]) != root);
+	}
+
+	/// Extrinsic which is valid when it is non-zero, signed by itself.
+	#[derive(Debug, PartialEq)]
+	struct SelfSigned(u64);
+
+	impl Slicable for SelfSigned {
+		fn decode<I: ::codec::Input>(input: &mut I) -> Option<Self> {
+			Slicable::decode(input).map(SelfSigned)
+		}
+		fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
+			self.0.using_encoded(f)
+		}
+	}
+
+	impl BlindCheckable for SelfSigned {
+		type Address = u64;
+		type Checked = u64;
+		fn sender(&self) -> &u64 { &self.0 }
+		fn check(self) -> Result<u64, &'static str> {
+			if self.0 != 0 { Ok(self.0) } else { Err("unsigned") }
+		}
+	}
+
+	#[test]
+	fn blind_checkable_weight_is_encoded_length() {
+		let lookup = |address: u64| -> Result<u64, &'static str> { Ok(address) };
+		assert_eq!(SelfSigned(7).check_with_weight(lookup), Ok((7, 8)));
+		assert_eq!(SelfSigned(0).check_with_weight(lookup), Err("unsigned"));
+	}
+
+	struct Three;
+	impl Get<u64> for Three {
+		fn get() -> u64 { 3 }
+	}
+
+	struct Thousand;
+	impl Get<u64> for Thousand {
+		fn get() -> u64 { 1_000 }
+	}
+
+	struct Nothing;
+	impl Get<u64> for Nothing {
+		fn get() -> u64 { 0 }
+	}
+
+	#[test]
+	fn rational_convert_scales_without_overflow() {
+		type ThreePerThousand = RationalConvert<Three, Thousand>;
+		assert_eq!(<ThreePerThousand as Convert<u64, u64>>::convert(1_999), 5);
+
+		// `value * 3` overflows `u64`.
+		let value = u64::max_value() / 2;
+		assert_eq!(<ThreePerThousand as Convert<u64, u64>>::convert(value), (value as u128 * 3 / 1_000) as u64);
+		assert_eq!(<ThreePerThousand as Convert<u128, u128>>::convert(u128::max_value()), u128::max_value() / 1_000 * 3 + u128::max_value() % 1_000 * 3 / 1_000);
+	}
+
+	#[test]
+	fn rational_convert_saturates() {
+		assert_eq!(<RationalConvert<Thousand, Three> as Convert<u64, u64>>::convert(u64::max_value() / 2), u64::max_value());
+	}
+
+	#[test]
+	fn rational_convert_with_zero_denominator_is_identity() {
+		assert_eq!(<RationalConvert<Three, Nothing> as Convert<u64, u64>>::convert(42), 42);
+	}
+
+	struct Two;
+	impl Get<u64> for Two {
+		fn get() -> u64 { 2 }
+	}
+
+	struct Unit;
+	impl Get<u64> for Unit {
+		fn get() -> u64 { 1 }
+	}
+
+	#[test]
+	fn rounded_rational_convert_rounds_to_nearest() {
+		let truncated = |value: u64| <RationalConvert<Unit, Two> as Convert<u64, u64>>::convert(value);
+		let rounded = |value: u64| <RoundedRationalConvert<Unit, Two> as Convert<u64, u64>>::convert(value);
+		assert_eq!((truncated(5), rounded(5)), (2, 3));
+		assert_eq!((truncated(4), rounded(4)), (2, 2));
+
+		type ThreePerThousand = RoundedRationalConvert<Three, Thousand>;
+		assert_eq!(<ThreePerThousand as Convert<u64, u64>>::convert(1_999), 6);
+		assert_eq!(<ThreePerThousand as Convert<u64, u64>>::convert(1_833), 5);
+		assert_eq!(<ThreePerThousand as Convert<u64, u64>>::convert(1_834), 6);
+	}
+
+	#[test]
+	fn rounded_rational_convert_saturates_and_handles_zero_denominator() {
+		assert_eq!(<RoundedRationalConvert<Thousand, Three> as Convert<u64, u64>>::convert(u64::max_value() / 2), u64::max_value());
+		assert_eq!(<RoundedRationalConvert<Three, Thousand> as Convert<u128, u128>>::convert(u128::max_value()), u128::max_value() / 1_000 * 3 + 1);
+		assert_eq!(<RoundedRationalConvert<Three, Nothing> as Convert<u64, u64>>::convert(42), 42);
+	}
+
+	#[test]
+	fn relay_block_maps_to_para_slot() {
+		let slot = |block: u64| <RelayToParaSlot<Three> as Convert<u64, u64>>::convert(block);
+		assert_eq!((0..7).map(slot).collect::<Vec<_>>(), vec![0, 0, 0, 1, 1, 1, 2]);
+		assert_eq!(<RelayToParaSlot<Thousand> as Convert<u32, u64>>::convert(2_999u32), 2);
+		assert_eq!(<RelayToParaSlot<Thousand> as Convert<u32, u64>>::convert(3_000u32), 3);
+		assert_eq!(<RelayToParaSlot<Nothing> as Convert<u64, u64>>::convert(42), 0);
+	}
+
+	#[test]
+	fn percent_of_value() {
+		assert_eq!(Percent::from_percent(50).mul(200u64), 100);
+		assert_eq!(Percent::from_percent(33).mul(10u32), 3);
+		assert_eq!(Percent::from_percent(100).mul(u64::max_value()), u64::max_value());
+		assert_eq!(Percent::zero().mul(200u64), 0);
+		assert_eq!(Percent::one().mul(200u64), 200);
+		assert_eq!(Percent::from_percent(50).mul(1u128 << 100), 1u128 << 99);
+	}
+
+	#[test]
+	fn percent_clamps_to_hundred() {
+		assert_eq!(Percent::from_percent(150), Percent::one());
+		assert_eq!(Percent::from_percent(255).mul(200u64), 200);
+		assert_eq!(Percent::from_percent(70) + Percent::from_percent(70), Percent::one());
+		assert_eq!(Percent::from_percent(50) * Percent::from_percent(50), Percent::from_percent(25));
+	}
+
+	#[test]
+	fn integers_multiply_by_per_things() {
+		assert_eq!(1_000_000u64 * ::Permill::from_parts(1), 1);
+		assert_eq!(999_999u64 * ::Permill::from_parts(1), 0);
+		assert_eq!(250u32 * Percent::from_percent(20), 50);
+		assert_eq!(7u8 * Percent::from_parts(50), 3);
+		assert_eq!(Percent::from_parts(250), Percent::one());
+		assert_eq!(::Permill::from_parts(2_000_000), ::Permill::from_percent(100));
+	}
+
+	#[test]
+	fn large_balances_do_not_overflow() {
+		assert_eq!(u128::max_value() * ::Permill::from_percent(100), u128::max_value());
+		assert_eq!(u128::max_value() * Percent::from_percent(50), u128::max_value() / 2);
+		assert_eq!(u64::max_value() * ::Permill::from_parts(999_999), u64::max_value() - 18_446_744_073_710);
+	}
+
+	#[test]
+	fn integers_divide_by_per_things() {
+		assert_eq!(50u32 / Percent::from_percent(20), 250);
+		assert_eq!(1u64 / ::Permill::from_parts(1), 1_000_000);
+		assert_eq!(200u8 / Percent::from_percent(50), 255);
+		assert_eq!(u128::max_value() / Percent::from_percent(50), u128::max_value());
+		assert_eq!(10u64 / Percent::zero(), u64::max_value());
+	}
+
+	#[test]
+	fn per_things_keep_the_sign_of_negative_values() {
+		assert_eq!(Percent::from_percent(50).mul_of(-200i64), -100);
+		assert_eq!(Percent::from_percent(33).mul_of(-10i32), -3);
+		assert_eq!(Percent::from_percent(50).mul_of(200i32), 100);
+		assert_eq!(Percent::from_percent(50).mul_of(i8::min_value()), -64);
+		assert_eq!(::Permill::from_percent(100).mul_of(i128::min_value()), i128::min_value());
+
+		assert_eq!(Percent::from_percent(50).div_of(-50i32), -100);
+		assert_eq!(Percent::from_percent(50).div_of(-100i8), i8::min_value());
+		assert_eq!(Percent::from_percent(50).div_of(i128::min_value()), i128::min_value());
+		assert_eq!(Percent::zero().div_of(-10i64), i64::min_value());
+	}
+
+	struct Fifty;
+	impl Get<u64> for Fifty {
+		fn get() -> u64 { 50 }
+	}
+
+	struct Hundred;
+	impl Get<u64> for Hundred {
+		fn get() -> u64 { 100 }
+	}
+
+	type LinearFee = TargetedFeeAdjustment<Fifty, Hundred>;
+	type SteepFee = TargetedFeeAdjustment<Fifty, Thousand>;
+
+	#[test]
+	fn fee_adjustment_at_ideal_fullness_is_base_fee() {
+		assert_eq!(LinearFee::adjust(1_000u64, Percent::from_percent(50)), 1_000);
+		assert_eq!(SteepFee::adjust(1_000u64, Percent::from_percent(50)), 1_000);
+		assert_eq!(LinearFee::convert((u128::max_value(), Percent::from_percent(50))), u128::max_value());
+	}
+
+	#[test]
+	fn fee_adjustment_of_empty_block_does_not_go_negative() {
+		assert_eq!(LinearFee::adjust(1_000u64, Percent::zero()), 500);
+		assert_eq!(SteepFee::adjust(1_000u64, Percent::zero()), 0);
+		assert_eq!(SteepFee::adjust(1_000u64, Percent::from_percent(40)), 0);
+		assert_eq!(SteepFee::adjust(1_000u64, Percent::from_percent(45)), 500);
+	}
+
+	#[test]
+	fn fee_adjustment_of_full_block_saturates() {
+		assert_eq!(LinearFee::adjust(1_000u64, Percent::one()), 1_500);
+		assert_eq!(SteepFee::adjust(1_000u64, Percent::one()), 6_000);
+		assert_eq!(SteepFee::adjust(u64::max_value(), Percent::one()), u64::max_value());
+		assert_eq!(SteepFee::convert((u128::max_value() / 2, Percent::one())), u128::max_value());
+	}
+
+	#[test]
+	fn fee_adjustment_of_signed_fees_is_clamped() {
+		assert_eq!(LinearFee::adjust(1_000i64, Percent::one()), 1_500);
+		assert_eq!(LinearFee::adjust(1_000i64, Percent::zero()), 500);
+		assert_eq!(SteepFee::adjust(i32::max_value(), Percent::one()), i32::max_value());
+		assert_eq!(SteepFee::adjust(1_000i16, Percent::zero()), 0);
+		assert_eq!(LinearFee::adjust(-1_000i64, Percent::one()), 0);
+	}
+
+	fn display<T: RuntimeDisplay>(value: T, len: usize) -> String {
+		let mut buf = vec![0u8; len];
+		let written = value.fmt_to(&mut buf);
+		String::from_utf8(buf[..written].to_vec()).unwrap()
+	}
+
+	#[test]
+	fn runtime_display_writes_decimal_numbers() {
+		assert_eq!(display(0u8, 8), "0");
+		assert_eq!(display(42u32, 8), "42");
+		assert_eq!(display(u64::max_value(), 32), "18446744073709551615");
+		assert_eq!(display(-7i32, 8), "-7");
+		assert_eq!(display(i128::min_value(), 64), "-170141183460469231731687303715884105728");
+	}
+
+	#[test]
+	fn runtime_display_truncates_to_buffer() {
+		assert_eq!(display(123456u64, 3), "123");
+		assert_eq!(display(-42i8, 1), "-");
+		assert_eq!(display(42u8, 0), "");
+	}
+
+	#[test]
+	fn runtime_display_writes_hash_as_hex() {
+		let expected = format!("{}01", "00".repeat(31));
+		assert_eq!(display(H256::from(1), 64), expected);
+		assert_eq!(display(H256::from(1), 128), expected);
+		assert_eq!(display(BlakeTwo256::hash(b""), 64), "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8");
+		assert_eq!(display(BlakeTwo256::hash(b""), 4), "0e57");
+	}
 }
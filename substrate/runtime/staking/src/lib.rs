@@ -927,7 +927,9 @@ impl<'a, 'b: 'a, T: Trait> contract::Ext for StakingExt<'a, 'b, T> {
 impl<T: Trait> MakePayment<T::AccountId> for Module<T> {
 	fn make_payment(transactor: &T::AccountId, encoded_len: usize) -> Result {
 		let b = Self::free_balance(transactor);
-		let transaction_fee = Self::transaction_base_fee() + Self::transaction_byte_fee() * <T::Balance as As<usize>>::sa(encoded_len);
+		let transaction_fee = Self::transaction_byte_fee().checked_mul(&<T::Balance as As<usize>>::sa(encoded_len))
+			.and_then(|byte_fee| byte_fee.checked_add(&Self::transaction_base_fee()))
+			.ok_or("transaction fee overflow")?;
 		if b < transaction_fee {
 			return Err("not enough funds for transaction fee");
 		}
@@ -393,3 +393,20 @@ fn transferring_incomplete_reserved_balance_should_work() {
 		assert_eq!(Staking::free_balance(&2), 42);
 	});
 }
+
+#[test]
+fn overflowing_transaction_fee_is_rejected() {
+	with_externalities(&mut new_test_ext(0, 1, 3, 1, false), || {
+		<FreeBalance<Test>>::insert(1, u64::max_value());
+		<TransactionByteFee<Test>>::put(u64::max_value() / 2);
+		assert_noop!(<Staking as MakePayment<u64>>::make_payment(&1, 3), "transaction fee overflow");
+	});
+}
+
+#[test]
+fn storage_key_matches_free_balance_key() {
+	use primitives::traits::{BlakeTwo256, Hashing};
+
+	let key = BlakeTwo256::storage_key(b"sta:", b"bal:", &[&1u64.encode()]);
+	assert_eq!(key, runtime_io::twox_128(&<FreeBalance<Test>>::key_for(&1u64)).to_vec());
+}
@@ -240,6 +240,61 @@ pub fn proof_check<Exec: CodeExecutor>(
 	execute(&backend, overlay, exec, method, call_data)
 }
 
+/// Generate storage read proof.
+pub fn prove_read<B: TryIntoTrieBackend>(
+	backend: B,
+	key: &[u8],
+) -> Result<(Option<Vec<u8>>, Vec<Vec<u8>>), Box<Error>>
+{
+	let trie_backend = backend.try_into_trie_backend()
+		.ok_or_else(|| Box::new(ExecutionError::UnableToGenerateProof) as Box<Error>)?;
+	let proving_backend = proving_backend::ProvingBackend::new(trie_backend);
+	let result = proving_backend.storage(key).map_err(|e| Box::new(e) as Box<Error>)?;
+	Ok((result, proving_backend.extract_proof()))
+}
+
+/// Check storage read proof, generated by `prove_read` call.
+pub fn read_proof_check(
+	root: [u8; 32],
+	proof: Vec<Vec<u8>>,
+	key: &[u8],
+) -> Result<Option<Vec<u8>>, Box<Error>>
+{
+	let backend = proving_backend::create_proof_check_backend(root.into(), proof)?;
+	backend.storage(key).map_err(|e| Box::new(e) as Box<Error>)
+}
+
+/// Generate single storage read proof for several keys. Values are returned in the order of `keys`.
+pub fn prove_read_batch<B: TryIntoTrieBackend>(
+	backend: B,
+	keys: &[Vec<u8>],
+) -> Result<(Vec<Option<Vec<u8>>>, Vec<Vec<u8>>), Box<Error>>
+{
+	let trie_backend = backend.try_into_trie_backend()
+		.ok_or_else(|| Box::new(ExecutionError::UnableToGenerateProof) as Box<Error>)?;
+	let proving_backend = proving_backend::ProvingBackend::new(trie_backend);
+	let result = keys.iter()
+		.map(|key| proving_backend.storage(key))
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(|e| Box::new(e) as Box<Error>)?;
+	Ok((result, proving_backend.extract_proof()))
+}
+
+/// Check storage read proof, generated by `prove_read_batch` call. Values are returned in the
+/// order of `keys`.
+pub fn read_batch_proof_check(
+	root: [u8; 32],
+	proof: Vec<Vec<u8>>,
+	keys: &[Vec<u8>],
+) -> Result<Vec<Option<Vec<u8>>>, Box<Error>>
+{
+	let backend = proving_backend::create_proof_check_backend(root.into(), proof)?;
+	keys.iter()
+		.map(|key| backend.storage(key))
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(|e| Box::new(e) as Box<Error>)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -343,4 +398,34 @@ mod tests {
 		assert_eq!(remote_result, vec![66]);
 		assert_eq!(remote_result, local_result);
 	}
+
+	#[test]
+	fn prove_read_and_proof_check_works() {
+		// fetch read proof from 'remote' full node
+		let remote_backend = trie_backend::tests::test_trie();
+		let remote_root = remote_backend.storage_root(::std::iter::empty()).0;
+		let remote_proof = prove_read(remote_backend, b"value2").unwrap().1;
+		// check proof locally
+		let local_result = read_proof_check(remote_root, remote_proof.clone(), b"value2").unwrap();
+		let wrong_root_result = read_proof_check([42; 32], remote_proof, b"value2");
+		// check that results are correct
+		assert_eq!(local_result, Some(vec![24]));
+		assert!(wrong_root_result.is_err());
+	}
+
+	#[test]
+	fn prove_read_batch_and_proof_check_works() {
+		let keys = vec![b"value2".to_vec(), b"non-existing-key".to_vec(), b"key".to_vec()];
+
+		// fetch read proof from 'remote' full node
+		let remote_backend = trie_backend::tests::test_trie();
+		let remote_root = remote_backend.storage_root(::std::iter::empty()).0;
+		let remote_proof = prove_read_batch(remote_backend, &keys).unwrap().1;
+		// check proof locally
+		let local_result = read_batch_proof_check(remote_root, remote_proof.clone(), &keys).unwrap();
+		let wrong_root_result = read_batch_proof_check([42; 32], remote_proof, &keys);
+		// check that results are correct and ordered as keys
+		assert_eq!(local_result, vec![Some(vec![24]), None, Some(b"value".to_vec())]);
+		assert!(wrong_root_result.is_err());
+	}
 }